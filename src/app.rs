@@ -1,230 +1,909 @@
-use crate::collection::system_components::SystemComponent;
-use crate::collection::{collect, system_components::Hostname};
-use crate::logos::LogoKind;
+use crate::collection::diagnostics::{Diagnostic, Status};
+use crate::collection::info::HostnameInfo;
+use crate::collection::metrics::MetricsSampler;
+use crate::collection::{collect, collect_components, hostname, CollectedNode, CollectionContext};
+use crate::color_support::ColorSupport;
+use crate::config::Config;
+use crate::history::MetricsHistory;
+use crate::keymap::{Action, Keymap};
+use crate::layout::{Direction, Fit, SystemGraph, Zones};
+use crate::logos::image::{GraphicsProtocol, ImageLogo, DEFAULT_CELL};
+use crate::logos::Logo;
+use crate::theme::Theme;
 use anyhow::Result;
 use ratatui::{
     backend::Backend,
-    crossterm::event::{self, KeyCode, KeyEventKind},
-    layout::{Constraint, Layout},
+    crossterm::{
+        event::{self, KeyEventKind, MouseButton, MouseEventKind},
+        terminal,
+    },
+    layout::{Constraint, Layout, Position, Rect},
     prelude::Margin,
-    style::{Color, Style, Stylize},
-    text::Text,
-    widgets::{block::Title, Block, Borders, Clear, Paragraph},
+    style::{Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{block::Title, Block, Borders, Clear, Paragraph, Sparkline},
+    Frame,
 };
-use std::time::Instant;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
 use tracing::trace;
-use tui_nodes::{NodeGraph, NodeLayout};
+use tui_nodes::{Connection, NodeGraph, NodeLayout};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-struct AppState<'a> {
-    current_logo: LogoKind,
+/// Samples kept of each metric in `--watch` mode, more than the panel is ever wide.
+const HISTORY_LENGTH: usize = 120;
+/// Columns the history panel takes up next to the graph, borders included.
+const HISTORY_PANEL_WIDTH: u16 = 32;
+/// Lines the detail popup scrolls by for each notch of the mouse wheel.
+const WHEEL_LINES: i32 = 3;
+
+pub(crate) struct AppState<'a> {
+    /// Logos `CycleLogo` goes through, the configured one first.
+    logos: Vec<Logo>,
+    /// Index of the logo in use.
+    logo: usize,
+    /// The logo colored for the current theme.
     logo_text: Text<'a>,
-    logo_text_width: usize,
-    logo_text_height: usize,
-    fg_color: Color,
+    /// Drawn in place of the configured logo, the first one, on terminals that show images.
+    pub image: Option<ImageLogo>,
+    /// Themes `CycleColor` goes through, the configured one first.
+    themes: Vec<Theme>,
+    /// Index of the theme in use.
+    theme: usize,
+    show_diagnostics: bool,
+    show_help: bool,
+    /// Key hints shown under the graph, `None` when they're hidden.
+    pub footer: Option<String>,
+    /// Index of the node with focus, if any has it yet.
+    focused: Option<usize>,
+    /// How far the focused node's detail popup is scrolled, while it's open.
+    detail_scroll: Option<u16>,
     pub needs_to_redraw: bool,
 }
 
-impl<'a> From<crate::args::Args> for AppState<'a> {
-    fn from(value: crate::args::Args) -> Self {
-        Self::new(value.logo_kind, value.fg_color.into())
+impl<'a> AppState<'a> {
+    /// State for drawing what `config` asks for, on the system `ctx` describes.
+    pub(crate) fn from_config(config: &Config, ctx: &CollectionContext) -> Result<Self> {
+        Ok(Self::new(config.logos(&ctx.os_ids())?, config.themes()))
     }
-}
 
-impl<'a> AppState<'a> {
-    pub fn new(logo_kind: LogoKind, fg_color: Color) -> Self {
-        let (logo_text, logo_text_width, logo_text_height) = Self::generate_logo_data(logo_kind);
-        let logo_text = ratatui::text::Text::from(logo_text);
+    pub(crate) fn new(logos: Vec<Logo>, themes: Vec<Theme>) -> Self {
+        let logo_text = logos[0].text(&themes[0]);
 
         Self {
-            current_logo: logo_kind,
+            logos,
+            logo: 0,
             logo_text,
-            logo_text_width,
-            logo_text_height,
-            fg_color,
+            image: None,
+            themes,
+            theme: 0,
+            show_diagnostics: false,
+            show_help: false,
+            footer: None,
+            focused: None,
+            detail_scroll: None,
             needs_to_redraw: true,
         }
     }
-    pub fn update_logo(&mut self, logo_kind: LogoKind) {
-        if self.current_logo == logo_kind {
-            return;
-        }
+    /// The logo in use.
+    fn logo(&self) -> &Logo {
+        &self.logos[self.logo]
+    }
 
-        let (logo_text, logo_text_width, logo_text_height) = Self::generate_logo_data(logo_kind);
-        self.logo_text = ratatui::text::Text::from(logo_text);
-        self.logo_text_width = logo_text_width;
-        self.logo_text_height = logo_text_height;
-        self.current_logo = logo_kind;
+    pub(crate) fn logo_height(&self) -> u16 {
+        self.logo().height() as u16
     }
 
-    fn update_fg_color(&mut self, new_color: Color) {
-        self.fg_color = new_color;
+    /// The logo image, while the logo it's in place of is the one in use.
+    fn image(&self) -> Option<&ImageLogo> {
+        self.image.as_ref().filter(|_| self.logo == 0)
     }
 
-    fn generate_logo_data(logo_kind: LogoKind) -> (String, usize, usize) {
-        logo_kind.get_rendered()
+    /// Whether anything is open over the graph and logo.
+    fn has_overlay(&self) -> bool {
+        self.show_help || self.show_diagnostics || self.detail_scroll.is_some()
+    }
+
+    /// Colors to draw everything in.
+    fn theme(&self) -> Theme {
+        self.themes[self.theme]
     }
 
     fn cycle_next_logo(&mut self) {
-        match self.current_logo {
-            LogoKind::Shadow => self.update_logo(LogoKind::Graffiti),
-            LogoKind::Graffiti => self.update_logo(LogoKind::Shadow),
-        }
+        self.logo = (self.logo + 1) % self.logos.len();
+        self.logo_text = self.logo().text(&self.theme());
     }
     fn cycle_next_color(&mut self) {
-        match self.fg_color {
-            Color::Rgb(255, 241, 164) => self.update_fg_color(Color::LightMagenta),
-            Color::LightMagenta => self.update_fg_color(Color::Rgb(255, 241, 164)),
-            _ => unreachable!("This color shouldn't be used."),
+        self.theme = (self.theme + 1) % self.themes.len();
+        self.logo_text = self.logo().text(&self.theme());
+    }
+
+    /// Moves focus along the graph's connections, to the first node if none had it yet.
+    fn move_focus(&mut self, graph: &SystemGraph, direction: Direction) {
+        self.focused = match self.focused {
+            Some(node) => graph.neighbor(node, direction).or(Some(node)),
+            None => (!graph.nodes.is_empty()).then_some(0),
+        };
+    }
+
+    /// Does what `action` means on whatever is showing, breaking when it's time to quit.
+    fn apply(
+        &mut self,
+        action: Action,
+        graph: &SystemGraph,
+        keymap: &Keymap,
+        screen: Rect,
+    ) -> ControlFlow<()> {
+        match action {
+            Action::Quit => return ControlFlow::Break(()),
+            Action::ToggleHelp => self.show_help = !self.show_help,
+            Action::Back if self.show_help => self.show_help = false,
+            // everything else waits until the help is closed
+            _ if self.show_help => {}
+            Action::ToggleFooter => {
+                self.footer = match self.footer {
+                    Some(_) => None,
+                    None => Some(keymap.hint()),
+                }
+            }
+            Action::ToggleDiagnostics => self.show_diagnostics = !self.show_diagnostics,
+            Action::CycleColor => self.cycle_next_color(),
+            Action::CycleLogo => self.cycle_next_logo(),
+            Action::Select | Action::Back if self.detail_scroll.is_some() => {
+                self.detail_scroll = None
+            }
+            Action::Up if self.detail_scroll.is_some() => self.scroll_details(graph, screen, -1),
+            Action::Down if self.detail_scroll.is_some() => self.scroll_details(graph, screen, 1),
+            Action::PageUp => self.scroll_details(graph, screen, -i32::from(screen.height / 2)),
+            Action::PageDown => self.scroll_details(graph, screen, i32::from(screen.height / 2)),
+            Action::Top => self.scroll_details(graph, screen, i32::MIN / 2),
+            Action::Bottom => self.scroll_details(graph, screen, i32::MAX / 2),
+            // the popup keeps focus where it is
+            _ if self.detail_scroll.is_some() => {}
+            Action::Left => self.move_focus(graph, Direction::Left),
+            Action::Right => self.move_focus(graph, Direction::Right),
+            Action::Up => self.move_focus(graph, Direction::Up),
+            Action::Down => self.move_focus(graph, Direction::Down),
+            Action::Next => self.cycle_focus(graph, true),
+            Action::Previous => self.cycle_focus(graph, false),
+            Action::Select if self.focused.is_some() => self.detail_scroll = Some(0),
+            Action::Select => {}
+            Action::Back => self.focused = None,
         }
+        ControlFlow::Continue(())
+    }
+
+    /// Scrolls the detail popup by `lines`, up to where its last line is at the bottom.
+    fn scroll_details(&mut self, graph: &SystemGraph, screen: Rect, lines: i32) {
+        let (Some(scroll), Some(node)) = (self.detail_scroll, self.focused) else {
+            return;
+        };
+        let (area, text) = detail_layout(&graph.nodes[node], screen);
+        let max_scroll = text
+            .len()
+            .saturating_sub(area.height.saturating_sub(2).into()) as i32;
+        self.detail_scroll = Some((scroll as i32 + lines).clamp(0, max_scroll) as u16);
+    }
+
+    /// Does what a left click at `position` means: the clicked node gets focus and its details
+    /// open, the logo switches to the next one, and anywhere else closes what's open.
+    fn click(&mut self, position: Position, zones: &Zones, graph: &SystemGraph, screen: Rect) {
+        if self.show_help {
+            self.show_help = false;
+            return;
+        }
+        if let (Some(_), Some(node)) = (self.detail_scroll, self.focused) {
+            // clicks inside the popup leave it open
+            if detail_layout(&graph.nodes[node], screen)
+                .0
+                .contains(position)
+            {
+                return;
+            }
+        }
+
+        if let Some(node) = zones.node_at(position) {
+            self.focused = Some(node);
+            self.detail_scroll = Some(0);
+        } else if zones.logo.contains(position) {
+            self.cycle_next_logo();
+        } else {
+            self.detail_scroll = None;
+        }
+    }
+
+    /// Moves focus to the next node in collection order, or the previous one, wrapping around.
+    fn cycle_focus(&mut self, graph: &SystemGraph, forward: bool) {
+        let count = graph.nodes.len();
+        if count == 0 {
+            return;
+        }
+        self.focused = Some(match (self.focused, forward) {
+            (Some(node), true) => (node + 1) % count,
+            (Some(node), false) => (node + count - 1) % count,
+            (None, true) => 0,
+            (None, false) => count - 1,
+        });
     }
 }
 
-pub(crate) fn app<T: Backend>(
-    mut terminal: ratatui::Terminal<T>,
-    args: crate::args::Args,
-) -> Result<()> {
-    let now = Instant::now();
-    let (nodes, links) = collect(args.visual_toggles)?;
-    let elapsed = now.elapsed().as_millis();
+/// Everything the TUI shows, collected and checked before the terminal is taken over so a
+/// failure leaves the terminal as it was.
+pub(crate) struct App<'a> {
+    graph: SystemGraph,
+    watch: Option<Watch>,
+    diagnostics: Vec<Diagnostic>,
+    hostname: HostnameInfo,
+    keymap: Keymap,
+    color_support: ColorSupport,
+    state: AppState<'a>,
+}
 
-    let hostname = Hostname::collect_info(&args.visual_toggles)?
-        .into_iter()
-        .next()
-        .unwrap();
+impl<'a> App<'a> {
+    /// Collects the system `ctx` describes and sets up drawing it as `config` asks.
+    pub(crate) fn new(ctx: &CollectionContext, config: &Config) -> Result<Self> {
+        let now = Instant::now();
+        let reports = collect_components(ctx, &config.enabled_components());
+        let (nodes, links) = collect(&reports, config)?;
+        let watch = config
+            .watch
+            .then(|| Watch::new(ctx, config.watch_interval(), nodes.clone()));
+        let graph = SystemGraph::new(nodes, links);
+        let diagnostics: Vec<_> = reports.iter().map(Diagnostic::from).collect();
+        let hostname = hostname(ctx);
+        trace!("collection took {}ms", now.elapsed().as_millis());
 
-    trace!("collection took {elapsed}ms");
+        let keymap = config.keymap()?;
+        let color_support = ColorSupport::detect(config.color, true);
+        trace!("drawing with {color_support:?} colors");
+        let mut state = AppState::from_config(config, ctx)?;
+        state.footer = config.footer.then(|| keymap.hint());
+        if let Some(path) = &config.logo_image {
+            if let Some(protocol) = GraphicsProtocol::detect(config.graphics) {
+                // still on the normal screen, so a bad image is reported where it can be read
+                let cell = match terminal::window_size() {
+                    Ok(size) if size.width > 0 && size.columns > 0 => {
+                        (size.width / size.columns, size.height / size.rows.max(1))
+                    }
+                    _ => DEFAULT_CELL,
+                };
+                trace!("drawing the logo image with {protocol:?} at {cell:?} pixels per cell");
+                state.image = Some(ImageLogo::load(path, protocol, cell)?);
+            }
+        }
 
-    let mut app_state = AppState::from(args);
+        Ok(Self {
+            graph,
+            watch,
+            diagnostics,
+            hostname,
+            keymap,
+            color_support,
+            state,
+        })
+    }
 
-    loop {
-        let frame_start = Instant::now();
-        if app_state.needs_to_redraw {
-            let graph_nodes = nodes
-                .iter()
-                .map(|node| {
-                    NodeLayout::new((node.width, node.height))
-                        .with_title(node.title)
-                        .with_border_style(Style::new().fg(app_state.fg_color))
-                        .with_border_type(ratatui::widgets::BorderType::Rounded)
-                })
-                .collect();
-
-            let connections = links
-                .iter()
-                .map(|node| node.with_line_style(Style::new().fg(app_state.fg_color)))
-                .collect();
+    /// Draws into `terminal` and handles input until the user quits.
+    pub(crate) fn run<T: Backend + Write>(self, mut terminal: ratatui::Terminal<T>) -> Result<()> {
+        let Self {
+            mut graph,
+            mut watch,
+            diagnostics,
+            hostname,
+            keymap,
+            color_support,
+            state: mut app_state,
+        } = self;
+        let mut zones = Zones::default();
+        // where the logo image was last drawn
+        let mut placed = None;
+
+        loop {
+            let frame_start = Instant::now();
+            if let Some(nodes) = watch.as_mut().and_then(Watch::refresh) {
+                // static nodes stay as they are, and ratatui only redraws the cells that changed
+                let changed = graph.update(nodes);
+                if !changed.is_empty() {
+                    let titles: Vec<_> = changed
+                        .iter()
+                        .map(|index| graph.nodes[*index].title.as_str())
+                        .collect();
+                    trace!("refreshed {}", titles.join(", "));
+                }
+                // the history panel moves on with every sample
+                app_state.needs_to_redraw = true;
+            }
+            if app_state.needs_to_redraw {
+                terminal.draw(|frame| {
+                    let history = watch.as_ref().map(|watch| &watch.history);
+                    zones = draw(frame, &app_state, &graph, &hostname, history);
+                    if let (Some(scroll), Some(node)) = (app_state.detail_scroll, app_state.focused)
+                    {
+                        draw_details(frame, &app_state, &graph.nodes[node], scroll);
+                    }
+                    if app_state.show_diagnostics {
+                        draw_diagnostics(frame, &app_state, &diagnostics);
+                    }
+                    if app_state.show_help {
+                        draw_help(frame, &app_state, &keymap);
+                    }
+                    color_support.adapt_buffer(frame.buffer_mut());
+                })?;
+                app_state.needs_to_redraw = false;
 
-            trace!("copied graph nodes {:?}", frame_start.elapsed());
+                if let Some(image) = &app_state.image {
+                    // overlays go over the logo, the image would cover them
+                    let placement = zones.image.filter(|_| !app_state.has_overlay());
+                    if placement != placed || !image.persists() {
+                        let backend = terminal.backend_mut();
+                        image.clear(backend)?;
+                        if let Some(area) = placement {
+                            image.draw(backend, area.as_position())?;
+                        }
+                        Backend::flush(backend)?;
+                        placed = placement;
+                    }
+                }
 
-            terminal.draw(|frame| {
                 trace!(
-                    "------------------------ frame draw started {:?}",
+                    "------------------------ frame draw over {:?}",
                     frame_start.elapsed()
                 );
-                let area = frame.area().inner(Margin::new(1, 0));
-
-                let [header_area, body_area] = Layout::vertical([
-                    Constraint::Length(app_state.logo_text_height as u16),
-                    Constraint::Fill(1),
-                ])
-                .areas(area);
-
-                let [logo_area] =
-                    Layout::horizontal([Constraint::Length(app_state.logo_text_width as u16)])
-                        .flex(ratatui::layout::Flex::Center)
-                        .areas(header_area);
-
-                frame.render_widget(Clear, logo_area);
-                frame.render_widget(
-                    app_state.logo_text.clone().fg(app_state.fg_color),
-                    logo_area,
-                );
-                trace!("logo drawn {:?}", frame_start.elapsed());
+            }
+            if event::poll(Duration::from_millis(16))? {
+                trace!("polled for event {:?}", frame_start.elapsed());
+                let size = terminal.size()?;
+                let screen = Rect::new(0, 0, size.width, size.height);
+                match event::read()? {
+                    event::Event::Key(key) => {
+                        if let Some(action) = keymap
+                            .action(&key)
+                            .filter(|_| key.kind == KeyEventKind::Press)
+                        {
+                            if app_state.apply(action, &graph, &keymap, screen).is_break() {
+                                break;
+                            }
+                        }
+                    }
+                    event::Event::Mouse(mouse) => {
+                        let position = Position::new(mouse.column, mouse.row);
+                        match mouse.kind {
+                            MouseEventKind::Down(MouseButton::Left) => {
+                                app_state.click(position, &zones, &graph, screen)
+                            }
+                            MouseEventKind::ScrollUp => {
+                                app_state.scroll_details(&graph, screen, -WHEEL_LINES)
+                            }
+                            MouseEventKind::ScrollDown => {
+                                app_state.scroll_details(&graph, screen, WHEEL_LINES)
+                            }
+                            // moving the mouse around doesn't change anything on screen
+                            _ => continue,
+                        }
+                    }
+                    // the screen is cleared for the new size, image and all
+                    event::Event::Resize(..) => placed = None,
+                    _ => {}
+                }
+                app_state.needs_to_redraw = true;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Samples live metrics in `--watch` mode and puts them on the collected nodes.
+struct Watch {
+    sampler: MetricsSampler,
+    interval: Duration,
+    next_sample: Instant,
+    /// Nodes as collected, before any metrics were added.
+    nodes: Vec<CollectedNode>,
+    history: MetricsHistory,
+}
+
+impl Watch {
+    fn new(ctx: &CollectionContext, interval: Duration, nodes: Vec<CollectedNode>) -> Self {
+        Self {
+            sampler: MetricsSampler::new(ctx.clone()),
+            interval,
+            next_sample: Instant::now(),
+            nodes,
+            history: MetricsHistory::new(HISTORY_LENGTH),
+        }
+    }
+
+    /// The nodes with fresh metrics, once every interval.
+    fn refresh(&mut self) -> Option<Vec<CollectedNode>> {
+        let now = Instant::now();
+        if now < self.next_sample {
+            return None;
+        }
+        self.next_sample = now + self.interval;
 
-                let window_widget = Block::new()
+        let metrics = self.sampler.sample();
+        trace!("sampled metrics in {:?}", now.elapsed());
+        self.history.record(&metrics);
+        Some(
+            self.nodes
+                .iter()
+                .map(|node| node.with_metrics(&metrics))
+                .collect(),
+        )
+    }
+}
+
+/// Draws the logo, hostname frame and system graph into `frame`, with the metrics' `history`
+/// beside the graph when there's one and room for it. Returns where the logo and nodes went.
+pub(crate) fn draw(
+    frame: &mut Frame,
+    app_state: &AppState,
+    graph: &SystemGraph,
+    hostname: &HostnameInfo,
+    history: Option<&MetricsHistory>,
+) -> Zones {
+    let frame_start = Instant::now();
+    trace!("------------------------ frame draw started");
+    let area = frame.area().inner(Margin::new(1, 0));
+
+    let [area, footer_area] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(app_state.footer.is_some().into()),
+    ])
+    .areas(area);
+    if let Some(footer) = &app_state.footer {
+        frame.render_widget(
+            Paragraph::new(footer.as_str())
+                .fg(app_state.theme().body)
+                .dim()
+                .centered(),
+            footer_area,
+        );
+    }
+
+    // the image only where it fits, the ASCII logo otherwise
+    let image = app_state
+        .image()
+        .filter(|image| image.size().0 <= area.width);
+    let (logo_width, logo_height) = match image {
+        Some(image) => image.size(),
+        None => (app_state.logo().width() as u16, app_state.logo_height()),
+    };
+
+    let [header_area, body_area] =
+        Layout::vertical([Constraint::Length(logo_height), Constraint::Fill(1)]).areas(area);
+
+    let [logo_area] = Layout::horizontal([Constraint::Length(logo_width)])
+        .flex(ratatui::layout::Flex::Center)
+        .areas(header_area);
+
+    // the image is written over the blank area once the frame is on screen
+    frame.render_widget(Clear, logo_area);
+    if image.is_none() {
+        frame.render_widget(app_state.logo_text.clone(), logo_area);
+    }
+    trace!("logo drawn {:?}", frame_start.elapsed());
+
+    let window_widget = Block::new()
+        .border_type(ratatui::widgets::BorderType::Rounded)
+        .border_style(Style::new().fg(app_state.theme().border))
+        .borders(Borders::TOP)
+        .title_style(Style::new().fg(app_state.theme().hostname))
+        .title(Title::from(format!(" {hostname} ")).alignment(ratatui::layout::Alignment::Center));
+    frame.render_widget(&window_widget, body_area);
+    trace!("window frame drawn {:?}", frame_start.elapsed());
+
+    let body_area = window_widget.inner(body_area).inner(Margin::new(0, 1));
+
+    // the panel only gets the room the graph can spare without getting any less detailed
+    let (body_area, history) = match history {
+        Some(history) => {
+            let [graph_area, panel_area] =
+                Layout::horizontal([Constraint::Fill(1), Constraint::Length(HISTORY_PANEL_WIDTH)])
+                    .areas(body_area);
+            match graph.fit(graph_area.as_size()) {
+                fit @ Fit::Graph(_) if fit == graph.fit(body_area.as_size()) => {
+                    (graph_area, Some((history, panel_area)))
+                }
+                _ => (body_area, None),
+            }
+        }
+        None => (body_area, None),
+    };
+    if let Some((history, panel_area)) = history {
+        draw_history(frame, app_state, history, panel_area);
+    }
+
+    let nodes = match graph.fit(body_area.as_size()) {
+        Fit::Graph(nodes) => {
+            trace!("drawing graph of {} nodes", nodes.len());
+            draw_graph(frame, app_state, nodes, &graph.links, body_area)
+        }
+        Fit::List => {
+            trace!("window too small for graph, drawing list");
+            draw_list(frame, app_state, &graph.nodes, body_area)
+        }
+        Fit::TooSmall => {
+            trace!("window too small {:?}", frame_start.elapsed());
+            frame.render_widget(
+                Paragraph::new("Window too small. Resize it to show system graph.")
+                    .fg(app_state.theme().error)
+                    .centered(),
+                body_area,
+            );
+            vec![]
+        }
+    };
+    trace!("body drawn {:?}", frame_start.elapsed());
+
+    Zones {
+        logo: logo_area,
+        image: image.map(|_| logo_area),
+        nodes,
+    }
+}
+
+/// Draws `nodes` as a graph in `area` and returns each node's area, borders included.
+fn draw_graph(
+    frame: &mut Frame,
+    app_state: &AppState,
+    nodes: &[CollectedNode],
+    links: &[Connection],
+    area: Rect,
+) -> Vec<Rect> {
+    let graph_nodes = nodes
+        .iter()
+        .enumerate()
+        .map(|(idx, node)| {
+            let layout = NodeLayout::new((node.width, node.height)).with_title(&node.title);
+            if app_state.focused == Some(idx) {
+                layout
+                    .with_border_style(Style::new().fg(app_state.theme().border).bold())
+                    .with_border_type(ratatui::widgets::BorderType::Double)
+            } else {
+                layout
+                    .with_border_style(Style::new().fg(app_state.theme().border))
+                    .with_border_type(ratatui::widgets::BorderType::Rounded)
+            }
+        })
+        .collect();
+
+    let connections = links
+        .iter()
+        .map(|node| node.with_line_style(Style::new().fg(app_state.theme().edge)))
+        .collect();
+
+    let mut system_info_nodes_graph = NodeGraph::new(
+        graph_nodes,
+        connections,
+        area.width.into(),
+        area.height.into(),
+    );
+    system_info_nodes_graph.calculate();
+
+    let zones = system_info_nodes_graph.split(area);
+    for (idx, ea_zone) in zones.iter().enumerate() {
+        frame.render_widget(
+            Paragraph::new(nodes[idx].body.clone())
+                .centered()
+                .fg(app_state.theme().body),
+            *ea_zone,
+        );
+    }
+    frame.render_stateful_widget(system_info_nodes_graph, area, &mut ());
+
+    // `tui_nodes` draws titles in the border's style, recolor them where they ended up
+    let zones: Vec<_> = zones.into_iter().map(Zones::with_borders).collect();
+    for (zone, node) in zones.iter().zip(nodes) {
+        let width = (node.title.width() as u16).min(zone.width.saturating_sub(2));
+        let title_area = Rect::new(zone.x + 1, zone.y, width, zone.height.min(1));
+        frame
+            .buffer_mut()
+            .set_style(title_area, Style::new().fg(app_state.theme().title));
+    }
+    zones
+}
+
+/// Sparklines of each core's usage, then memory and swap, as many cores as fit in `area`.
+fn draw_history(frame: &mut Frame, app_state: &AppState, history: &MetricsHistory, area: Rect) {
+    let block = Block::bordered()
+        .border_type(ratatui::widgets::BorderType::Rounded)
+        .border_style(Style::new().fg(app_state.theme().border))
+        .title_style(Style::new().fg(app_state.theme().title))
+        .title(Title::from(" History ").alignment(ratatui::layout::Alignment::Center));
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let rows = history.rows(inner.height as usize);
+
+    let row_areas = Layout::vertical(vec![Constraint::Length(1); rows.len()]).split(inner);
+    for ((label, values), row_area) in rows.into_iter().zip(row_areas.iter()) {
+        let [label_area, sparkline_area, latest_area] = Layout::horizontal([
+            Constraint::Length(4),
+            Constraint::Fill(1),
+            Constraint::Length(5),
+        ])
+        .areas(*row_area);
+        let data: Vec<u64> = values.last(sparkline_area.width as usize).collect();
+        let latest = values
+            .latest()
+            .map(|latest| format!("{latest:>4}%"))
+            .unwrap_or_default();
+
+        frame.render_widget(Paragraph::new(label).fg(app_state.theme().body), label_area);
+        frame.render_widget(
+            Paragraph::new(latest).fg(app_state.theme().body),
+            latest_area,
+        );
+        frame.render_widget(
+            Sparkline::default()
+                .data(&data)
+                .max(100)
+                .style(Style::new().fg(app_state.theme().body)),
+            sparkline_area,
+        );
+    }
+}
+
+/// Fallback for areas too small for the graph: one `title body` line per node. Returns each
+/// node's line.
+fn draw_list(
+    frame: &mut Frame,
+    app_state: &AppState,
+    nodes: &[CollectedNode],
+    area: Rect,
+) -> Vec<Rect> {
+    let lines: Vec<Line> = nodes
+        .iter()
+        .enumerate()
+        .map(|(idx, node)| {
+            let line = Line::from(vec![
+                Span::from(node.title.as_str())
+                    .fg(app_state.theme().title)
+                    .bold(),
+                // bodies of several lines, like one per disk, share the node's line
+                Span::from(format!(
+                    " {}",
+                    node.body
+                        .lines()
+                        .map(str::trim)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            ]);
+            if app_state.focused == Some(idx) {
+                line.reversed()
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines).fg(app_state.theme().body), area);
+    (0..nodes.len() as u16)
+        .map(|idx| {
+            if idx < area.height {
+                Rect::new(area.x, area.y + idx, area.width, 1)
+            } else {
+                Rect::default()
+            }
+        })
+        .collect()
+}
+
+/// Popup with everything collected about `node`, scrolled down by `scroll` lines.
+fn draw_details(frame: &mut Frame, app_state: &AppState, node: &CollectedNode, scroll: u16) {
+    let (area, lines) = detail_layout(node, frame.area());
+    let max_scroll = lines
+        .len()
+        .saturating_sub(area.height.saturating_sub(2).into()) as u16;
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .fg(app_state.theme().body)
+            .scroll((scroll.min(max_scroll), 0))
+            .block(
+                Block::bordered()
                     .border_type(ratatui::widgets::BorderType::Rounded)
-                    .border_style(Style::new().fg(app_state.fg_color))
-                    .borders(Borders::TOP)
+                    .border_style(Style::new().fg(app_state.theme().border))
+                    .padding(ratatui::widgets::Padding::horizontal(1))
+                    .title_style(Style::new().fg(app_state.theme().title))
                     .title(
-                        Title::from(" ".to_owned() + hostname.as_ref() + " ")
+                        Title::from(node.title.as_str())
                             .alignment(ratatui::layout::Alignment::Center),
-                    );
-                frame.render_widget(&window_widget, body_area);
-                trace!("window frame drawn {:?}", frame_start.elapsed());
+                    ),
+            ),
+        area,
+    );
+}
 
-                let body_area = window_widget.inner(body_area).inner(Margin::new(0, 1));
+/// Widest the detail popup gets, borders included, however wide the screen.
+const MAX_DETAIL_WIDTH: u16 = 96;
 
-                let mut system_info_nodes_graph = NodeGraph::new(
-                    graph_nodes,
-                    connections,
-                    body_area.width.into(),
-                    body_area.height.into(),
-                );
+/// Where the detail popup for `node` goes on `screen`, and its lines with values wrapped to fit.
+fn detail_layout(node: &CollectedNode, screen: Rect) -> (Rect, Vec<Line<'static>>) {
+    let rows = match &node.info {
+        Some(info) => info.details(),
+        // placeholders only have their message to show
+        None => vec![("Status".to_string(), node.body.trim().to_string())],
+    };
 
-                trace!("node graph created {:?}", frame_start.elapsed());
-
-                // TODO: do this better... please...
-                // horrid panic suppression code...
-                std::panic::set_hook(Box::new(|_| {}));
-                trace!("noop panic hook set {:?}", frame_start.elapsed());
-                let test = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                    trace!("node graph calculate(start) {:?}", frame_start.elapsed());
-                    system_info_nodes_graph.calculate();
-                    trace!("node graph calculate(done) {:?}", frame_start.elapsed());
-                }));
-                trace!("catch_unwind() {:?}", frame_start.elapsed());
-                let _ = std::panic::take_hook();
-                trace!("panic hook restored {:?}", frame_start.elapsed());
-                // horridness over!
-
-                if test.is_err() {
-                    trace!("window too small {:?}", frame_start.elapsed());
-                    frame.render_widget(
-                        Paragraph::new("Window too small. Resize it to show system graph.")
-                            .red()
-                            .centered(),
-                        body_area,
-                    );
-                } else {
-                    trace!("window good {:?}", frame_start.elapsed());
-                    let zones = system_info_nodes_graph.split(body_area);
-                    trace!("zones obtained {:?}", frame_start.elapsed());
-                    for (idx, ea_zone) in zones.into_iter().enumerate() {
-                        frame.render_widget(
-                            Paragraph::new(nodes[idx].body.clone())
-                                .centered()
-                                .fg(app_state.fg_color),
-                            ea_zone,
-                        );
-                        trace!("zone {idx} drawn {:?}", frame_start.elapsed());
-                    }
-                    frame.render_stateful_widget(system_info_nodes_graph, body_area, &mut ());
-                    trace!("node graph widget drawn {:?}", frame_start.elapsed());
-                }
+    // borders and a column of padding on each side
+    let width = screen.width.saturating_sub(4).min(MAX_DETAIL_WIDTH);
+    let label_width = rows
+        .iter()
+        .map(|(label, _)| label.width())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let value_width = (width.saturating_sub(4) as usize)
+        .saturating_sub(label_width)
+        .max(1);
 
-                app_state.needs_to_redraw = false;
-            })?;
+    let mut lines = vec![];
+    for (label, value) in rows {
+        for (idx, chunk) in wrap(&value, value_width).into_iter().enumerate() {
+            let label = if idx == 0 { label.as_str() } else { "" };
+            lines.push(Line::from(vec![
+                Span::from(format!("{label:label_width$}")).bold(),
+                Span::from(chunk),
+            ]));
+        }
+    }
 
-            trace!(
-                "------------------------ frame draw over {:?}",
-                frame_start.elapsed()
-            );
+    let height = (lines.len() as u16 + 2).min(screen.height.saturating_sub(2));
+    (centered(screen, width, height), lines)
+}
+
+/// A `width` by `height` rect in the middle of `area`, shrunk to fit it.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Length(height.min(area.height))])
+        .flex(ratatui::layout::Flex::Center)
+        .areas(area);
+    let [area] = Layout::horizontal([Constraint::Length(width.min(area.width))])
+        .flex(ratatui::layout::Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Splits `text` into lines of at most `width` columns, breaking between words where it can
+/// and inside words longer than a line.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in text.split_whitespace() {
+        let line = lines.last_mut().unwrap();
+        if !line.is_empty() && line.width() + 1 + word.width() > width {
+            lines.push(String::new());
+        } else if !line.is_empty() {
+            line.push(' ');
         }
-        if event::poll(std::time::Duration::from_millis(16))? {
-            trace!("polled for event {:?}", frame_start.elapsed());
-            if let event::Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') => break,
-                        KeyCode::Char('c') => app_state.cycle_next_color(),
-                        KeyCode::Char('l') => app_state.cycle_next_logo(),
-                        _ => {}
-                    }
-                }
+        for c in word.chars() {
+            let line = lines.last_mut().unwrap();
+            if !line.is_empty() && line.width() + c.width().unwrap_or(0) > width {
+                lines.push(String::new());
             }
-            app_state.needs_to_redraw = true;
+            lines.last_mut().unwrap().push(c);
         }
     }
+    lines
+}
+
+/// Overlay listing every action with the keys bound to it.
+fn draw_help(frame: &mut Frame, app_state: &AppState, keymap: &Keymap) {
+    let rows: Vec<_> = Action::iter()
+        .map(|action| {
+            let keys: Vec<_> = keymap.keys(action).map(|key| key.to_string()).collect();
+            (keys.join(", "), action.description())
+        })
+        .collect();
+    let keys_width = rows.iter().map(|(keys, _)| keys.width()).max().unwrap_or(0) + 2;
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(keys, description)| {
+            Line::from(vec![
+                Span::from(format!("{keys:keys_width$}")).bold(),
+                Span::from(description),
+            ])
+        })
+        .collect();
+
+    // borders and a column of padding on each side
+    let area = frame.area();
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let area = centered(area, width, lines.len() as u16 + 2);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).fg(app_state.theme().body).block(
+            Block::bordered()
+                .border_type(ratatui::widgets::BorderType::Rounded)
+                .border_style(Style::new().fg(app_state.theme().border))
+                .padding(ratatui::widgets::Padding::horizontal(1))
+                .title_style(Style::new().fg(app_state.theme().title))
+                .title(Title::from(" Keys ").alignment(ratatui::layout::Alignment::Center)),
+        ),
+        area,
+    );
+}
+
+/// Overlay listing every collector with its status, errors and timing.
+fn draw_diagnostics(frame: &mut Frame, app_state: &AppState, diagnostics: &[Diagnostic]) {
+    let lines: Vec<Line> = diagnostics
+        .iter()
+        .flat_map(|diagnostic| {
+            let text = diagnostic.to_string();
+            let style = match diagnostic.status {
+                Status::Ok => Style::new().fg(app_state.theme().body),
+                Status::Failed | Status::TimedOut => Style::new().fg(app_state.theme().error),
+            };
+            text.lines()
+                .map(|line| Line::styled(line.to_string(), style))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    // borders and a column of padding on each side
+    let area = frame.area();
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let area = centered(area, width, lines.len() as u16 + 2);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::bordered()
+                .border_type(ratatui::widgets::BorderType::Rounded)
+                .border_style(Style::new().fg(app_state.theme().border))
+                .padding(ratatui::widgets::Padding::horizontal(1))
+                .title_style(Style::new().fg(app_state.theme().title))
+                .title(Title::from(" Diagnostics ").alignment(ratatui::layout::Alignment::Center)),
+        ),
+        area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::fixtures::{fixture, offline};
+    use crate::logos::image::GraphicsChoice;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_app_without_hostname() -> TestResult {
+        let root = fixture(&[]);
+        let app = App::new(&offline(&root), &Config::default())?;
+        assert_eq!(app.hostname.hostname, "unknown");
+        Ok(())
+    }
+
+    #[test]
+    fn test_app_rejects_bad_logo_image() {
+        // no terminal exists yet, so a bad image can't leave one behind
+        let root = fixture(&[]);
+        let config = Config {
+            logo_image: Some("/nonexistent/logo.png".into()),
+            graphics: GraphicsChoice::Kitty,
+            ..Config::default()
+        };
+        let error = App::new(&offline(&root), &config).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Failed to read logo image /nonexistent/logo.png"
+        );
+    }
 
-    Ok(())
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("Failed to read /sys/class/net", 12),
+            vec!["Failed to", "read", "/sys/class/n", "et"]
+        );
+        assert_eq!(wrap("one two", 7), vec!["one two"]);
+        assert_eq!(wrap("", 7), vec![""]);
+    }
 }
@@ -1,37 +1,149 @@
 use clap::Parser;
-use ratatui::style::Color;
+use std::path::PathBuf;
 
-use crate::logos::LogoKind;
+use crate::collection::CollectionContext;
+use crate::color_support::ColorChoice;
+use crate::logos::image::GraphicsChoice;
+use crate::theme::{ThemeColor, ThemeName};
 
 #[derive(Parser, Debug)]
 #[command(version)]
 pub(crate) struct Args {
-    #[arg(short, long = "logo", default_value = "shadow")]
-    pub logo_kind: LogoKind,
-    #[arg(short = 'c', long = "fg-color", default_value = "solaara-gold")]
-    pub fg_color: FgColor,
+    /// Logo to show, a built-in one, one from the logos directory or `auto` for the
+    /// operating system's [default: shadow]
+    #[arg(short, long, value_name = "NAME")]
+    pub logo: Option<String>,
+    /// Show the logo in this file instead, plain text or the colored `.toml` format
+    #[arg(long, value_name = "PATH")]
+    pub logo_file: Option<PathBuf>,
+    /// Show this PNG in place of the logo on terminals that can show images
+    #[arg(long, value_name = "PATH")]
+    pub logo_image: Option<PathBuf>,
+    /// How to show `--logo-image`, `auto` picks what the terminal is known to support and
+    /// falls back to the ASCII logo [default: auto]
+    #[arg(long, value_name = "PROTOCOL")]
+    pub graphics: Option<GraphicsChoice>,
+    /// Built-in theme to draw in: solaara-gold, light-magenta, nord, gruvbox, dracula or
+    /// terminal [default: solaara-gold]
+    #[arg(short, long, value_name = "NAME")]
+    pub theme: Option<ThemeName>,
+    /// One color for everything but errors, like `#fff1a4`, `rgb(255, 241, 164)`,
+    /// `light-magenta` or a 256-color index
+    #[arg(short = 'c', long = "fg-color", value_name = "COLOR")]
+    pub fg_color: Option<ThemeColor>,
+    /// When to use colors, `auto` leaves them out when NO_COLOR is set or output isn't a
+    /// terminal [default: auto]
+    #[arg(long, value_name = "WHEN")]
+    pub color: Option<ColorChoice>,
+
+    /// Read settings from this file instead of the default config file
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Print the default config file, with every setting documented, and exit
+    #[arg(long)]
+    pub print_default_config: bool,
+
+    /// Print the collected information as JSON and exit
+    #[arg(long, conflicts_with = "print")]
+    pub json: bool,
+
+    /// Print the logo and system graph once to stdout and exit
+    #[arg(long)]
+    pub print: bool,
+
+    /// Width to render at in print mode, defaults to the terminal's width
+    #[arg(long, requires = "print")]
+    pub width: Option<u16>,
+
+    /// List every collector with its status, errors and timing, then exit
+    #[arg(long, conflicts_with_all = ["json", "print"])]
+    pub diagnose: bool,
+
+    /// Keep refreshing memory, CPU usage, temperatures, uptime and load average
+    #[arg(long, conflicts_with_all = ["json", "print", "diagnose"])]
+    pub watch: bool,
+
+    /// Seconds between refreshes in watch mode [default: 2]
+    #[arg(long, value_name = "SECONDS")]
+    pub watch_interval: Option<f64>,
+
+    /// Describe the system installed under this directory instead of the running one
+    #[arg(long, value_name = "PATH")]
+    pub root: Option<PathBuf>,
 
     #[command(flatten, next_help_heading = "Visual Toggles")]
-    pub visual_toggles: VisualToggles,
+    pub toggles: ToggleArgs,
 }
 
-#[derive(Clone, Debug, Copy, clap::Args, Default)]
-pub(crate) struct VisualToggles {
-    #[arg(long)]
-    pub hide_terminal_version: bool,
+impl Args {
+    /// Where collectors should read the system from, `--root` or the running machine.
+    pub fn collection_context(&self, visual_toggles: VisualToggles) -> CollectionContext {
+        match &self.root {
+            Some(root) => CollectionContext::offline(root, visual_toggles),
+            None => CollectionContext::live(visual_toggles),
+        }
+    }
 }
 
-#[derive(Debug, clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum FgColor {
-    SolaaraGold,
-    LightMagenta,
+/// Flags turning toggles on or off whatever the config file says. Each comes with its
+/// opposite, and the last of the two given wins.
+#[derive(Clone, Debug, Copy, PartialEq, Eq, clap::Args, Default)]
+pub(crate) struct ToggleArgs {
+    /// Leave the terminal's version out of its name
+    #[arg(long, overrides_with = "show_terminal_version")]
+    hide_terminal_version: bool,
+    /// Show the terminal's version even when the config file hides it
+    #[arg(long, overrides_with = "hide_terminal_version")]
+    show_terminal_version: bool,
+    /// Show components that couldn't be collected as "unknown" instead of leaving them out
+    #[arg(long, overrides_with = "hide_unknown")]
+    show_unknown: bool,
+    /// Leave components that couldn't be collected out even when the config file shows them
+    #[arg(long, overrides_with = "show_unknown")]
+    hide_unknown: bool,
+    /// Leave the loopback interface out of the network links
+    #[arg(long, overrides_with = "show_loopback")]
+    hide_loopback: bool,
+    /// Show the loopback interface, which is left out by default
+    #[arg(long, overrides_with = "hide_loopback")]
+    show_loopback: bool,
+    /// Leave links that only exist in software, like docker0, veth and tun, out of the
+    /// network links
+    #[arg(long, overrides_with = "show_virtual_links")]
+    hide_virtual_links: bool,
+    /// Show links that only exist in software, which are left out by default
+    #[arg(long, overrides_with = "hide_virtual_links")]
+    show_virtual_links: bool,
 }
 
-impl From<FgColor> for Color {
-    fn from(value: FgColor) -> Self {
-        match value {
-            FgColor::SolaaraGold => Color::Rgb(255, 241, 164),
-            FgColor::LightMagenta => Color::LightMagenta,
-        }
+impl ToggleArgs {
+    pub fn hide_terminal_version(&self) -> Option<bool> {
+        switch(self.hide_terminal_version, self.show_terminal_version)
+    }
+
+    pub fn show_unknown(&self) -> Option<bool> {
+        switch(self.show_unknown, self.hide_unknown)
+    }
+
+    pub fn hide_loopback(&self) -> Option<bool> {
+        switch(self.hide_loopback, self.show_loopback)
+    }
+
+    pub fn hide_virtual_links(&self) -> Option<bool> {
+        switch(self.hide_virtual_links, self.show_virtual_links)
     }
 }
+
+/// What a pair of opposite flags asks for, `None` when neither was given.
+fn switch(on: bool, off: bool) -> Option<bool> {
+    (on || off).then_some(on)
+}
+
+/// Toggles that change what collectors report, gathered from the config file and flags.
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Default)]
+pub(crate) struct VisualToggles {
+    pub hide_terminal_version: bool,
+    pub hide_loopback: bool,
+    pub hide_virtual_links: bool,
+}
@@ -0,0 +1,150 @@
+//! Runs collectors on their own threads so one slow probe can't hold up the rest.
+
+use anyhow::{Error, Result};
+use std::fmt::{self, Display, Formatter};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Error a job resolves to when it didn't finish before its deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TimedOut(pub Duration);
+
+impl Display for TimedOut {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "timed out after {}ms", self.0.as_millis())
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+pub(super) type Job<T> = Box<dyn FnOnce() -> Result<T> + Send>;
+
+/// Runs every `(name, timeout, job)` concurrently and returns their results in the same order,
+/// along with how long each one took.
+///
+/// Jobs still running when their timeout runs out resolve to [`TimedOut`] and are left
+/// behind, whatever they were stuck on goes away with the process.
+pub(super) fn run_all<T: Send + 'static>(
+    jobs: Vec<(&'static str, Duration, Job<T>)>,
+) -> Vec<(Result<T>, Duration)> {
+    let start = Instant::now();
+    let (sender, receiver) = mpsc::channel();
+
+    let mut pending: Vec<_> = jobs
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (name, timeout, job))| {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                let result = job();
+                let elapsed = start.elapsed();
+                tracing::trace!("{name} took {}ms", elapsed.as_millis());
+                // nobody's listening anymore if we already gave up on this job
+                let _ = sender.send((idx, result, elapsed));
+            });
+            (idx, name, start + timeout)
+        })
+        .collect();
+    drop(sender);
+
+    let mut results: Vec<Option<(Result<T>, Duration)>> = pending.iter().map(|_| None).collect();
+    while let Some(deadline) = pending.iter().map(|(_, _, deadline)| *deadline).min() {
+        match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok((idx, result, elapsed)) => {
+                pending.retain(|(pending_idx, _, _)| *pending_idx != idx);
+                results[idx] = Some((result, elapsed));
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                let now = Instant::now();
+                pending.retain(|(idx, name, deadline)| {
+                    if *deadline > now {
+                        return true;
+                    }
+                    let timeout = *deadline - start;
+                    tracing::trace!("{name} timed out after {}ms", timeout.as_millis());
+                    results[*idx] = Some((Err(TimedOut(timeout).into()), timeout));
+                    false
+                });
+            }
+            // every job that's still pending panicked
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    results
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| (Err(Error::msg("Collector panicked.")), start.elapsed()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(delay: Duration, value: u32) -> Job<u32> {
+        Box::new(move || {
+            thread::sleep(delay);
+            Ok(value)
+        })
+    }
+
+    #[test]
+    fn test_slow_job_times_out() {
+        let start = Instant::now();
+        let results = run_all(vec![
+            (
+                "slow",
+                Duration::from_millis(50),
+                job(Duration::from_secs(5), 1),
+            ),
+            ("fast", Duration::from_secs(5), job(Duration::ZERO, 2)),
+        ]);
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(
+            results[0]
+                .0
+                .as_ref()
+                .unwrap_err()
+                .downcast_ref::<TimedOut>(),
+            Some(&TimedOut(Duration::from_millis(50)))
+        );
+        assert_eq!(results[1].0.as_ref().unwrap(), &2);
+        assert!(results[1].1 < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_keeps_job_order() {
+        let results = run_all(vec![
+            (
+                "first",
+                Duration::from_secs(5),
+                job(Duration::from_millis(30), 1),
+            ),
+            ("second", Duration::from_secs(5), job(Duration::ZERO, 2)),
+            (
+                "third",
+                Duration::from_secs(5),
+                Box::new(|| Err(Error::msg("nope"))),
+            ),
+        ]);
+
+        assert_eq!(results[0].0.as_ref().unwrap(), &1);
+        assert_eq!(results[1].0.as_ref().unwrap(), &2);
+        assert!(results[2].0.is_err());
+    }
+
+    #[test]
+    fn test_panicking_job() {
+        let results: Vec<(Result<u32>, _)> = run_all(vec![(
+            "panics",
+            Duration::from_secs(5),
+            Box::new(|| panic!("oh no")),
+        )]);
+
+        assert!(results[0].0.is_err());
+    }
+}
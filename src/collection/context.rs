@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::args::VisualToggles;
+
+/// Everything a collector is allowed to look at: a filesystem root to read `/proc`, `/sys`
+/// and `/etc` from, the environment and the process to start looking for the shell from.
+///
+/// The live context points at the running machine, other roots let collectors run against
+/// captured fixtures, mounted disk images or chroots. Only the Linux collectors honor the
+/// root and environment, the other platforms always describe the running machine.
+#[derive(Debug, Clone)]
+pub(crate) struct CollectionContext {
+    root: PathBuf,
+    env: HashMap<String, String>,
+    pid: Option<u32>,
+    pub visual_toggles: VisualToggles,
+}
+
+impl CollectionContext {
+    /// Describes the running machine and session.
+    pub fn live(visual_toggles: VisualToggles) -> Self {
+        Self {
+            root: PathBuf::from("/"),
+            env: std::env::vars().collect(),
+            pid: Some(std::process::id()),
+            visual_toggles,
+        }
+    }
+
+    /// Describes the system installed under `root`, with no session or processes to inspect.
+    pub fn offline(root: impl Into<PathBuf>, visual_toggles: VisualToggles) -> Self {
+        Self {
+            root: root.into(),
+            env: HashMap::new(),
+            pid: None,
+            visual_toggles,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_env<K: Into<String>, V: Into<String>>(
+        mut self,
+        env: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        self.env = env.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+        self
+    }
+
+    /// Sets the process whose ancestors are searched for the shell and terminal.
+    #[cfg(test)]
+    pub fn with_pid(mut self, pid: u32) -> Self {
+        self.pid = Some(pid);
+        self
+    }
+
+    /// Whether this context describes the machine we're running on.
+    pub fn is_live(&self) -> bool {
+        self.root == Path::new("/")
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    pub fn env(&self, key: &str) -> Option<&str> {
+        self.env
+            .get(key)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Resolves an absolute system path like `/proc/meminfo` under this context's root.
+    pub fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        self.root.join(path.strip_prefix("/").unwrap_or(path))
+    }
+
+    pub fn read_to_string(&self, path: impl AsRef<Path>) -> std::io::Result<String> {
+        std::fs::read_to_string(self.path(path))
+    }
+
+    /// The `KEY="value"` pairs of the system's `os-release` file, none if it has none.
+    pub fn os_release(&self) -> HashMap<String, String> {
+        ["/etc/os-release", "/usr/lib/os-release"]
+            .iter()
+            .find_map(|path| self.read_to_string(path).ok())
+            .map(|content| parse_os_release(&content))
+            .unwrap_or_default()
+    }
+
+    /// The os-release `ID` followed by the ones in `ID_LIKE`, closest match first.
+    pub fn os_ids(&self) -> Vec<String> {
+        let os_release = self.os_release();
+        let id = os_release.get("ID").map(String::as_str);
+        let like = os_release.get("ID_LIKE").map(String::as_str);
+        id.into_iter()
+            .chain(like.into_iter().flat_map(str::split_whitespace))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// Parses the `KEY="value"` lines of an `os-release` file.
+fn parse_os_release(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .or_else(|| {
+                    value
+                        .strip_prefix('\'')
+                        .and_then(|value| value.strip_suffix('\''))
+                })
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Fake system trees for collectors to run against in tests.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use std::fs;
+    use tempfile::TempDir;
+
+    use super::CollectionContext;
+    use crate::args::VisualToggles;
+
+    /// Writes a fake system tree made of `(path, content)` pairs.
+    pub fn fixture(files: &[(&str, &str)]) -> TempDir {
+        let root = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = root.path().join(path.trim_start_matches('/'));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    pub fn offline(root: &TempDir) -> CollectionContext {
+        CollectionContext::offline(root.path(), VisualToggles::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::{fixture, offline};
+
+    #[test]
+    fn test_os_ids_from_fixture() {
+        let root = fixture(&[(
+            "/etc/os-release",
+            "NAME=\"Linux Mint\"\nID=linuxmint\nID_LIKE=\"ubuntu debian\"\n",
+        )]);
+        assert_eq!(offline(&root).os_ids(), ["linuxmint", "ubuntu", "debian"]);
+
+        let root = fixture(&[]);
+        assert!(offline(&root).os_ids().is_empty());
+    }
+}
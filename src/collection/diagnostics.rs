@@ -0,0 +1,134 @@
+//! What happened to each collector, for `--diagnose` and the TUI's diagnostics overlay.
+
+use anyhow::Result;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+use super::{ComponentReport, TimedOut};
+
+/// How a collector's run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    Ok,
+    Failed,
+    TimedOut,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Status::Ok => "ok",
+            Status::Failed => "failed",
+            Status::TimedOut => "timed out",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Diagnostic {
+    pub component: &'static str,
+    pub status: Status,
+    /// Number of records collected, zero unless the collector succeeded.
+    pub records: usize,
+    /// The error and everything that caused it, outermost first.
+    pub error_chain: Vec<String>,
+    pub elapsed: Duration,
+}
+
+impl Diagnostic {
+    pub fn new<T>(component: &'static str, result: &Result<Vec<T>>, elapsed: Duration) -> Self {
+        let (status, records, error_chain) = match result {
+            Ok(records) => (Status::Ok, records.len(), vec![]),
+            Err(e) => (
+                if e.is::<TimedOut>() {
+                    Status::TimedOut
+                } else {
+                    Status::Failed
+                },
+                0,
+                e.chain().map(|cause| cause.to_string()).collect(),
+            ),
+        };
+
+        Self {
+            component,
+            status,
+            records,
+            error_chain,
+            elapsed,
+        }
+    }
+}
+
+impl From<&ComponentReport> for Diagnostic {
+    fn from(report: &ComponentReport) -> Self {
+        Self::new(report.kind.id(), &report.result, report.elapsed)
+    }
+}
+
+/// One line for the collector, then one more per error in the chain.
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<20} {:<9} {:>6}ms",
+            self.component,
+            self.status,
+            self.elapsed.as_millis()
+        )?;
+
+        match self.status {
+            Status::Ok => write!(
+                f,
+                "  {} record{}",
+                self.records,
+                if self.records == 1 { "" } else { "s" }
+            ),
+            _ => {
+                for (depth, error) in self.error_chain.iter().enumerate() {
+                    if depth == 0 {
+                        write!(f, "  {error}")?;
+                    } else {
+                        write!(f, "\n{:40}caused by: {error}", "")?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_error_chain() {
+        let result: Result<Vec<()>> = Err(std::io::Error::other("permission denied"))
+            .context("Failed to read /proc/cpuinfo.");
+        let diagnostic = Diagnostic::new("cpu", &result, Duration::from_millis(3));
+
+        assert_eq!(diagnostic.status, Status::Failed);
+        assert_eq!(
+            diagnostic.to_string(),
+            format!(
+                "cpu                  failed         3ms  Failed to read /proc/cpuinfo.\n{:40}caused by: permission denied",
+                ""
+            )
+        );
+    }
+
+    #[test]
+    fn test_timed_out_and_ok() {
+        let timed_out: Result<Vec<()>> = Err(TimedOut(Duration::from_secs(1)).into());
+        let diagnostic = Diagnostic::new("gpu", &timed_out, Duration::from_secs(1));
+        assert_eq!(diagnostic.status, Status::TimedOut);
+
+        let ok: Result<Vec<u8>> = Ok(vec![1, 2]);
+        assert_eq!(
+            Diagnostic::new("gpu", &ok, Duration::ZERO).to_string(),
+            "gpu                  ok             0ms  2 records"
+        );
+    }
+}
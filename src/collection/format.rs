@@ -0,0 +1,733 @@
+//! Formatting layer turning collected records into the text shown to users.
+
+use std::fmt::{Display, Formatter, Result};
+use std::time::Duration;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::info::{
+    BatteryInfo, BoardModelInfo, ComponentInfo, CpuInfo, DesktopEnvironmentInfo, DiskInfo,
+    DiskKind, Drive, GpuInfo, HostnameInfo, MemoryInfo, NetInfo, NetLink, OsInfo, ShellInfo,
+    TerminalInfo, WindowManagerInfo,
+};
+use super::metrics::Metrics;
+
+impl Display for CpuInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}x {}", self.threads, self.model)
+    }
+}
+
+impl Display for MemoryInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", human_bytes::human_bytes(self.total_bytes as f64))
+    }
+}
+
+impl Display for GpuInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match &self.vendor {
+            Some(vendor) => write!(f, "{vendor} {}", self.model),
+            None => write!(f, "{}", self.model),
+        }
+    }
+}
+
+impl Display for DiskInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        // one line per disk
+        for (idx, disk) in self.disks.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            disk.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Drive {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let name = self.model.as_ref().unwrap_or(&self.device);
+        let size = human_bytes::human_bytes(self.size_bytes as f64);
+        match self.kind {
+            Some(kind) => write!(f, "{name} ({size} {kind})"),
+            None => write!(f, "{name} ({size})"),
+        }
+    }
+}
+
+impl Display for DiskKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let kind = match self {
+            DiskKind::Nvme => "NVMe",
+            DiskKind::Ssd => "SSD",
+            DiskKind::Hdd => "HDD",
+            DiskKind::Virtual => "virtual",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+impl Display for BatteryInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.charge_percent {
+            Some(charge) => write!(f, "{} {charge}% ({})", self.name, self.state),
+            None => write!(f, "{} ({})", self.name, self.state),
+        }
+    }
+}
+
+impl Display for NetInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        // one line per link
+        for (idx, link) in self.links.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            link.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for NetLink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        // addresses say the most about a link that's in use, the state about one that isn't
+        let address = self
+            .ipv4
+            .first()
+            .or(self.ipv6.first())
+            .unwrap_or(&self.state);
+        match self.speed_mbps {
+            Some(speed) => write!(
+                f,
+                "{} {address} ({}, {})",
+                self.name,
+                self.kind,
+                speed_text(speed)
+            ),
+            None => write!(f, "{} {address} ({})", self.name, self.kind),
+        }
+    }
+}
+
+/// Link speed in the units NICs are sold with, like `1 Gb/s` or `2.5 Gb/s`.
+fn speed_text(mbps: u32) -> String {
+    if mbps >= 1000 {
+        format!("{} Gb/s", mbps as f64 / 1000.0)
+    } else {
+        format!("{mbps} Mb/s")
+    }
+}
+
+impl Display for BoardModelInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match &self.vendor {
+            // product names often repeat the vendor already
+            Some(vendor) if !self.name.starts_with(vendor.as_str()) => {
+                write!(f, "{vendor} {}", self.name)
+            }
+            _ => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl Display for OsInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.name)?;
+        if let Some(arch) = &self.arch {
+            write!(f, " ({arch})")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ShellInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Display for TerminalInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Display for WindowManagerInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Display for DesktopEnvironmentInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Display for HostnameInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.hostname)
+    }
+}
+
+impl Display for ComponentInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            ComponentInfo::Cpu(info) => info.fmt(f),
+            ComponentInfo::SystemMemory(info) => info.fmt(f),
+            ComponentInfo::Gpu(info) => info.fmt(f),
+            ComponentInfo::Disk(info) => info.fmt(f),
+            ComponentInfo::Battery(info) => info.fmt(f),
+            ComponentInfo::Net(info) => info.fmt(f),
+            ComponentInfo::BoardModel(info) => info.fmt(f),
+            ComponentInfo::OperatingSystem(info) => info.fmt(f),
+            ComponentInfo::CurrentShell(info) => info.fmt(f),
+            ComponentInfo::TerminalEmulator(info) => info.fmt(f),
+            ComponentInfo::WindowManager(info) => info.fmt(f),
+            ComponentInfo::DesktopEnvironment(info) => info.fmt(f),
+        }
+    }
+}
+
+/// Longest body a compacted node gets before it is cut off.
+const COMPACT_SUMMARY_WIDTH: usize = 24;
+
+impl ComponentInfo {
+    /// Shorter summary used when the full one doesn't fit on screen, cutting off each of
+    /// its lines.
+    pub fn compact_summary(&self) -> String {
+        let summary = match self {
+            ComponentInfo::Cpu(info) => {
+                format!("{}x {}", info.threads, compact_cpu_model(&info.model))
+            }
+            ComponentInfo::OperatingSystem(info) => info.name.clone(),
+            _ => self.to_string(),
+        };
+        summary
+            .lines()
+            .map(|line| truncate(line, COMPACT_SUMMARY_WIDTH))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl ComponentInfo {
+    /// Everything in the record as `(label, value)` rows, for the TUI's detail popup.
+    pub fn details(&self) -> Vec<(String, String)> {
+        let mut rows = Rows::default();
+        match self {
+            ComponentInfo::Cpu(info) => {
+                rows.add("Model", Some(&info.model));
+                rows.add("Vendor", info.vendor.as_ref());
+                rows.add(
+                    "Cores",
+                    Some(format!("{} ({} threads)", info.cores, info.threads)),
+                );
+                let mhz = |mhz: Option<u64>| mhz.map(|mhz| format!("{mhz} MHz"));
+                rows.add("Max frequency", mhz(info.frequency_mhz));
+                rows.add("Min frequency", mhz(info.min_frequency_mhz));
+                rows.add("Current frequency", mhz(info.current_frequency_mhz));
+                rows.add("Microcode", info.microcode.as_ref());
+                for cache in &info.caches {
+                    let kind = match cache.kind.as_str() {
+                        "Data" => "d",
+                        "Instruction" => "i",
+                        _ => "",
+                    };
+                    rows.add(
+                        format!("L{}{kind} cache", cache.level),
+                        Some(human_bytes::human_bytes(cache.size_bytes as f64)),
+                    );
+                }
+                rows.add(
+                    "Flags",
+                    (!info.flags.is_empty()).then(|| info.flags.join(" ")),
+                );
+            }
+            ComponentInfo::SystemMemory(info) => {
+                rows.add(
+                    "Total",
+                    Some(human_bytes::human_bytes(info.total_bytes as f64)),
+                );
+                rows.add(
+                    "Swap",
+                    info.swap_total_bytes
+                        .map(|swap| human_bytes::human_bytes(swap as f64)),
+                );
+                for slot in &info.slots {
+                    let description = match slot.size_bytes {
+                        Some(size) => [
+                            Some(human_bytes::human_bytes(size as f64)),
+                            slot.kind.clone(),
+                            slot.speed_mts.map(|speed| format!("{speed} MT/s")),
+                            slot.manufacturer.clone(),
+                            slot.part_number.clone(),
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                        None => "empty".to_string(),
+                    };
+                    rows.add(&slot.locator, Some(description));
+                }
+                for swap in &info.swap_devices {
+                    rows.add(
+                        &swap.path,
+                        Some(format!(
+                            "{}, {} used of {}",
+                            swap.kind,
+                            human_bytes::human_bytes(swap.used_bytes as f64),
+                            human_bytes::human_bytes(swap.size_bytes as f64)
+                        )),
+                    );
+                }
+            }
+            ComponentInfo::Gpu(info) => {
+                rows.add("Model", Some(&info.model));
+                rows.add("Vendor", info.vendor.as_ref());
+                rows.add("Driver", info.driver.as_ref());
+                rows.add("PCI id", Some(&info.pci_id));
+                let kind = match info.integrated {
+                    Some(true) => Some("integrated"),
+                    Some(false) => Some("discrete"),
+                    None => None,
+                };
+                rows.add("Type", kind);
+            }
+            ComponentInfo::Disk(info) => {
+                // each disk under its device, followed by what's mounted from it
+                for disk in &info.disks {
+                    let mut description = human_bytes::human_bytes(disk.size_bytes as f64);
+                    if let Some(kind) = disk.kind {
+                        description = format!("{description} {kind}");
+                    }
+                    if let Some(model) = &disk.model {
+                        description = format!("{model}, {description}");
+                    }
+                    if disk.removable {
+                        description.push_str(", removable");
+                    }
+                    rows.add(&disk.device, Some(description));
+                    for filesystem in &disk.filesystems {
+                        let mounted = format!("{} on {}", filesystem.fs_type, filesystem.source);
+                        let description = match (filesystem.used_bytes, filesystem.size_bytes) {
+                            (Some(used), Some(size)) => format!(
+                                "{mounted}, {} used of {}",
+                                human_bytes::human_bytes(used as f64),
+                                human_bytes::human_bytes(size as f64)
+                            ),
+                            _ => mounted,
+                        };
+                        rows.add(&filesystem.mount_point, Some(description));
+                    }
+                }
+            }
+            ComponentInfo::Battery(info) => {
+                rows.add("Name", Some(&info.name));
+                rows.add("Model", info.model.as_ref());
+                rows.add("Vendor", info.vendor.as_ref());
+                rows.add(
+                    "Charge",
+                    info.charge_percent.map(|charge| format!("{charge}%")),
+                );
+                rows.add("State", Some(&info.state));
+                rows.add(
+                    "Health",
+                    info.health_percent
+                        .map(|health| format!("{health}% of design capacity")),
+                );
+                rows.add("Cycles", info.cycles);
+                let ac = |online| if online { "plugged in" } else { "unplugged" };
+                rows.add("AC adapter", info.ac_online.map(ac));
+            }
+            ComponentInfo::Net(info) => {
+                // each link under its name, followed by its addresses
+                for link in &info.links {
+                    let mut description = link.kind.clone();
+                    if link.is_virtual && link.kind != "virtual" {
+                        description.push_str(" (virtual)");
+                    }
+                    description = format!("{description}, {}", link.state);
+                    if let Some(speed) = link.speed_mbps {
+                        description = format!("{description}, {}", speed_text(speed));
+                    }
+                    rows.add(&link.name, Some(description));
+                    rows.add("MAC", link.mac.as_ref());
+                    for address in &link.ipv4 {
+                        rows.add("IPv4", Some(address));
+                    }
+                    for address in &link.ipv6 {
+                        rows.add("IPv6", Some(address));
+                    }
+                }
+            }
+            ComponentInfo::BoardModel(info) => {
+                rows.add("Name", Some(&info.name));
+                rows.add("Vendor", info.vendor.as_ref());
+                rows.add("Version", info.version.as_ref());
+            }
+            ComponentInfo::OperatingSystem(info) => {
+                rows.add("Name", Some(&info.name));
+                rows.add("Version", info.version.as_ref());
+                rows.add("Build", info.build.as_ref());
+                rows.add("Architecture", info.arch.as_ref());
+                rows.add("Kernel", info.kernel.as_ref());
+                rows.add("Kernel build", info.kernel_build.as_ref());
+            }
+            ComponentInfo::CurrentShell(info) => {
+                rows.add("Name", Some(&info.name));
+                rows.add("Version", info.version.as_ref());
+                rows.add("Path", info.path.as_ref());
+                rows.add("PID", Some(info.pid.to_string()));
+            }
+            ComponentInfo::TerminalEmulator(TerminalInfo { name })
+            | ComponentInfo::WindowManager(WindowManagerInfo { name })
+            | ComponentInfo::DesktopEnvironment(DesktopEnvironmentInfo { name }) => {
+                rows.add("Name", Some(name));
+            }
+        }
+        rows.0
+    }
+}
+
+/// Detail rows being built up, skipping the values that weren't collected.
+#[derive(Default)]
+struct Rows(Vec<(String, String)>);
+
+impl Rows {
+    fn add(&mut self, label: impl Into<String>, value: Option<impl ToString>) {
+        if let Some(value) = value {
+            self.0.push((label.into(), value.to_string()));
+        }
+    }
+}
+
+impl Metrics {
+    /// Readings that belong on `info`'s node, `None` when there are none for it.
+    ///
+    /// Percentages are padded so nodes keep their size as the readings change.
+    pub fn describe(&self, info: &ComponentInfo) -> Option<String> {
+        let readings = match info {
+            ComponentInfo::Cpu(_) => [
+                self.cpu_usage.map(|usage| format!("{usage:>3.0}%")),
+                self.cpu_temperature.map(|temp| format!("{temp:.0}°C")),
+            ],
+            ComponentInfo::SystemMemory(_) => [
+                self.memory
+                    .filter(|(_, total)| *total > 0)
+                    .map(|(used, total)| format!("{:>3}% used", used * 100 / total)),
+                None,
+            ],
+            ComponentInfo::OperatingSystem(_) => [
+                self.uptime
+                    .map(|uptime| format!("up {}", format_uptime(uptime))),
+                self.load_average.map(|[one, ..]| format!("load {one:.2}")),
+            ],
+            _ => return None,
+        };
+
+        let readings: Vec<_> = readings.into_iter().flatten().collect();
+        (!readings.is_empty()).then(|| readings.join(" · "))
+    }
+}
+
+/// Two most significant units of `uptime`, like `3d 4h` or `12m`.
+fn format_uptime(uptime: Duration) -> String {
+    let minutes = uptime.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{minutes}m"),
+        (0, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h"),
+    }
+}
+
+/// Drops the trademark noise and clock speed most CPU model names come with.
+fn compact_cpu_model(model: &str) -> String {
+    let model = model.split(" @ ").next().unwrap_or(model);
+    model
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "CPU" | "Processor"))
+        .map(|word| {
+            word.replace("(R)", "")
+                .replace("(TM)", "")
+                .replace("(tm)", "")
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Cuts `text` down to `max_width` columns, marking the cut with an ellipsis.
+pub(crate) fn truncate(text: &str, max_width: usize) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+
+    let mut truncated = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width + 1 > max_width {
+            break;
+        }
+        truncated.push(c);
+        width += char_width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::info::{CpuCache, Filesystem};
+
+    /// The detail popup's rows as `label: value` lines.
+    fn details_lines(info: &ComponentInfo) -> Vec<String> {
+        info.details()
+            .into_iter()
+            .map(|(label, value)| format!("{label}: {value}"))
+            .collect()
+    }
+
+    #[test]
+    fn test_cpu_summary() {
+        let info = CpuInfo {
+            model: "AMD Ryzen 7 5800X 8-Core Processor".to_string(),
+            vendor: Some("AuthenticAMD".to_string()),
+            cores: 8,
+            threads: 16,
+            frequency_mhz: Some(3800),
+            ..Default::default()
+        };
+        assert_eq!(info.to_string(), "16x AMD Ryzen 7 5800X 8-Core Processor");
+        assert_eq!(
+            ComponentInfo::Cpu(info).compact_summary(),
+            "16x AMD Ryzen 7 5800X 8…"
+        );
+    }
+
+    #[test]
+    fn test_details() {
+        let info = ComponentInfo::Cpu(CpuInfo {
+            model: "AMD Ryzen 7 5800X 8-Core Processor".to_string(),
+            cores: 8,
+            threads: 16,
+            frequency_mhz: Some(3800),
+            caches: vec![CpuCache {
+                level: 1,
+                kind: "Instruction".to_string(),
+                size_bytes: 32 * 1024,
+            }],
+            flags: vec!["sse2".to_string(), "avx2".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            details_lines(&info),
+            vec![
+                "Model: AMD Ryzen 7 5800X 8-Core Processor",
+                "Cores: 8 (16 threads)",
+                "Max frequency: 3800 MHz",
+                "L1i cache: 32 KiB",
+                "Flags: sse2 avx2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_disk_summary_and_details() {
+        let mut info = DiskInfo {
+            disks: vec![
+                Drive {
+                    device: "/dev/nvme0n1".to_string(),
+                    model: Some("Samsung SSD 980 PRO 1TB".to_string()),
+                    size_bytes: 1024 * 1024 * 1024 * 1024,
+                    kind: Some(DiskKind::Nvme),
+                    removable: false,
+                    filesystems: vec![
+                        Filesystem {
+                            source: "/dev/nvme0n1p2".to_string(),
+                            mount_point: "/".to_string(),
+                            fs_type: "ext4".to_string(),
+                            size_bytes: Some(100 * 1024 * 1024 * 1024),
+                            used_bytes: Some(40 * 1024 * 1024 * 1024),
+                        },
+                        Filesystem {
+                            source: "/dev/nvme0n1p1".to_string(),
+                            mount_point: "/boot".to_string(),
+                            fs_type: "vfat".to_string(),
+                            size_bytes: None,
+                            used_bytes: None,
+                        },
+                    ],
+                },
+                Drive {
+                    device: "/dev/vdb".to_string(),
+                    model: None,
+                    size_bytes: 256 * 1024 * 1024 * 1024,
+                    kind: Some(DiskKind::Virtual),
+                    removable: true,
+                    filesystems: vec![],
+                },
+            ],
+        };
+        assert_eq!(
+            info.to_string(),
+            "Samsung SSD 980 PRO 1TB (1 TiB NVMe)\n/dev/vdb (256 GiB virtual)"
+        );
+        assert_eq!(
+            ComponentInfo::Disk(info.clone()).compact_summary(),
+            "Samsung SSD 980 PRO 1TB…\n/dev/vdb (256 GiB virtu…"
+        );
+        assert_eq!(
+            details_lines(&ComponentInfo::Disk(info.clone())),
+            vec![
+                "/dev/nvme0n1: Samsung SSD 980 PRO 1TB, 1 TiB NVMe",
+                "/: ext4 on /dev/nvme0n1p2, 40 GiB used of 100 GiB",
+                "/boot: vfat on /dev/nvme0n1p1",
+                "/dev/vdb: 256 GiB virtual, removable",
+            ]
+        );
+
+        info.disks.truncate(1);
+        info.disks[0].kind = None;
+        assert_eq!(info.to_string(), "Samsung SSD 980 PRO 1TB (1 TiB)");
+    }
+
+    #[test]
+    fn test_battery_summary_and_details() {
+        let mut info = BatteryInfo {
+            name: "BAT0".to_string(),
+            model: Some("5B10W13930".to_string()),
+            vendor: Some("SMP".to_string()),
+            charge_percent: Some(87),
+            state: "charging".to_string(),
+            health_percent: Some(88),
+            cycles: Some(312),
+            ac_online: Some(true),
+        };
+        assert_eq!(info.to_string(), "BAT0 87% (charging)");
+
+        assert_eq!(
+            details_lines(&ComponentInfo::Battery(info.clone())),
+            vec![
+                "Name: BAT0",
+                "Model: 5B10W13930",
+                "Vendor: SMP",
+                "Charge: 87%",
+                "State: charging",
+                "Health: 88% of design capacity",
+                "Cycles: 312",
+                "AC adapter: plugged in",
+            ]
+        );
+
+        info.charge_percent = None;
+        info.state = "unknown".to_string();
+        assert_eq!(info.to_string(), "BAT0 (unknown)");
+    }
+
+    #[test]
+    fn test_net_summary_and_details() {
+        let mut wired = NetLink {
+            name: "enp3s0".to_string(),
+            kind: "wired".to_string(),
+            is_virtual: false,
+            state: "up".to_string(),
+            mac: Some("a8:a1:59:12:34:56".to_string()),
+            ipv4: vec!["192.168.1.10/24".to_string()],
+            ipv6: vec!["fe80::aaa1:59ff:fe12:3456/64".to_string()],
+            speed_mbps: Some(2500),
+        };
+        let docker = NetLink {
+            name: "docker0".to_string(),
+            kind: "bridge".to_string(),
+            is_virtual: true,
+            state: "down".to_string(),
+            mac: None,
+            ipv4: vec![],
+            ipv6: vec![],
+            speed_mbps: None,
+        };
+        let info = NetInfo {
+            links: vec![wired.clone(), docker],
+        };
+        assert_eq!(
+            info.to_string(),
+            "enp3s0 192.168.1.10/24 (wired, 2.5 Gb/s)\ndocker0 down (bridge)"
+        );
+        assert_eq!(
+            details_lines(&ComponentInfo::Net(info)),
+            vec![
+                "enp3s0: wired, up, 2.5 Gb/s",
+                "MAC: a8:a1:59:12:34:56",
+                "IPv4: 192.168.1.10/24",
+                "IPv6: fe80::aaa1:59ff:fe12:3456/64",
+                "docker0: bridge (virtual), down",
+            ]
+        );
+
+        wired.ipv4.clear();
+        wired.speed_mbps = Some(100);
+        assert_eq!(
+            wired.to_string(),
+            "enp3s0 fe80::aaa1:59ff:fe12:3456/64 (wired, 100 Mb/s)"
+        );
+    }
+
+    #[test]
+    fn test_compact_cpu_model() {
+        assert_eq!(
+            compact_cpu_model("Intel(R) Core(TM) i7-8650U CPU @ 1.90GHz"),
+            "Intel Core i7-8650U"
+        );
+    }
+
+    #[test]
+    fn test_os_summary() {
+        let mut info = OsInfo {
+            name: "Arch Linux".to_string(),
+            version: None,
+            arch: Some("x86_64".to_string()),
+            kernel: None,
+            ..Default::default()
+        };
+        assert_eq!(info.to_string(), "Arch Linux (x86_64)");
+        info.arch = None;
+        assert_eq!(info.to_string(), "Arch Linux");
+    }
+
+    #[test]
+    fn test_describe_metrics() {
+        let metrics = Metrics {
+            memory: Some((1, 4)),
+            swap: None,
+            load_average: Some([0.5, 0.25, 0.125]),
+            uptime: Some(Duration::from_secs(3 * 86400 + 4 * 3600 + 59)),
+            cpu_usage: Some(7.4),
+            core_usage: vec![7.4],
+            cpu_temperature: None,
+        };
+        let os = ComponentInfo::OperatingSystem(OsInfo {
+            name: "Arch Linux".to_string(),
+            version: None,
+            arch: None,
+            kernel: None,
+            ..Default::default()
+        });
+        let memory = ComponentInfo::SystemMemory(MemoryInfo {
+            total_bytes: 4,
+            swap_total_bytes: None,
+            ..Default::default()
+        });
+
+        assert_eq!(metrics.describe(&os).unwrap(), "up 3d 4h · load 0.50");
+        assert_eq!(metrics.describe(&memory).unwrap(), " 25% used");
+        assert_eq!(format_uptime(Duration::from_secs(3600 + 120)), "1h 2m");
+        assert_eq!(Metrics::default().describe(&os), None);
+    }
+}
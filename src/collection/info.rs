@@ -0,0 +1,221 @@
+//! Typed records produced by the system component collectors.
+//!
+//! These hold the raw facts as they were collected; turning them into the
+//! strings shown in the graph is the job of the [`format`](super::format) module.
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct CpuInfo {
+    pub model: String,
+    pub vendor: Option<String>,
+    /// Physical cores.
+    pub cores: usize,
+    /// Logical cores (hardware threads).
+    pub threads: usize,
+    /// Advertised maximum clock, or the current one when that's all there is.
+    pub frequency_mhz: Option<u64>,
+    pub min_frequency_mhz: Option<u64>,
+    pub current_frequency_mhz: Option<u64>,
+    pub microcode: Option<String>,
+    /// Caches of the first core, some of them shared with the others.
+    pub caches: Vec<CpuCache>,
+    /// Feature flags as the kernel names them, like `avx2` or `neon`.
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct CpuCache {
+    pub level: u8,
+    /// `Data`, `Instruction` or `Unified`.
+    pub kind: String,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct MemoryInfo {
+    pub total_bytes: u64,
+    pub swap_total_bytes: Option<u64>,
+    /// Memory slots the firmware lists, empty ones included.
+    pub slots: Vec<MemorySlot>,
+    pub swap_devices: Vec<SwapDevice>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct MemorySlot {
+    pub locator: String,
+    /// `None` when nothing is installed in the slot.
+    pub size_bytes: Option<u64>,
+    /// `DDR4`, `LPDDR5` and the like.
+    pub kind: Option<String>,
+    pub speed_mts: Option<u16>,
+    pub manufacturer: Option<String>,
+    pub part_number: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct SwapDevice {
+    pub path: String,
+    /// `partition` or `file`.
+    pub kind: String,
+    pub size_bytes: u64,
+    pub used_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct GpuInfo {
+    pub vendor: Option<String>,
+    pub model: String,
+    pub driver: Option<String>,
+    /// `vendor:device` PCI ids in hex.
+    pub pci_id: String,
+    /// Whether this is an integrated GPU, if that could be told.
+    pub integrated: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct DiskInfo {
+    /// Every physical disk, shown together in one node.
+    pub disks: Vec<Drive>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct Drive {
+    /// Like `/dev/nvme0n1` or `/dev/sda`.
+    pub device: String,
+    pub model: Option<String>,
+    pub size_bytes: u64,
+    /// `None` when the disk doesn't say what it's made of.
+    pub kind: Option<DiskKind>,
+    pub removable: bool,
+    /// Filesystems mounted from the disk's partitions, or from devices built on it.
+    pub filesystems: Vec<Filesystem>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub(crate) enum DiskKind {
+    #[serde(rename = "NVMe")]
+    Nvme,
+    #[serde(rename = "SSD")]
+    Ssd,
+    #[serde(rename = "HDD")]
+    Hdd,
+    /// Handed to a virtual machine by its host, whatever that stores it on.
+    #[serde(rename = "virtual")]
+    Virtual,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct Filesystem {
+    /// Device the filesystem is mounted from, like `/dev/nvme0n1p2` or `/dev/mapper/root`.
+    pub source: String,
+    pub mount_point: String,
+    /// `ext4`, `btrfs`, `vfat` and the like.
+    pub fs_type: String,
+    /// How full it is, only known for the running system.
+    pub size_bytes: Option<u64>,
+    pub used_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct BatteryInfo {
+    /// Like `BAT0`.
+    pub name: String,
+    pub model: Option<String>,
+    pub vendor: Option<String>,
+    pub charge_percent: Option<u8>,
+    /// `charging`, `discharging`, `full`, `not charging` or `unknown`.
+    pub state: String,
+    /// How much of its design capacity the battery still holds when full.
+    pub health_percent: Option<u8>,
+    pub cycles: Option<u32>,
+    /// Whether a power adapter is plugged in, `None` when there's no adapter to ask.
+    pub ac_online: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct NetInfo {
+    /// Every network interface left after the toggles, shown together in one node.
+    pub links: Vec<NetLink>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct NetLink {
+    /// Like `enp3s0` or `wlan0`.
+    pub name: String,
+    /// `wired`, `wireless`, `bridge`, `VLAN`, `virtual` or `loopback`.
+    pub kind: String,
+    /// Whether the link only exists in software, like docker0, veth and tun devices.
+    pub is_virtual: bool,
+    /// `up`, `down`, `dormant` and the like.
+    pub state: String,
+    pub mac: Option<String>,
+    /// Addresses with their prefix length, like `192.168.1.10/24`, only known for the
+    /// running system.
+    pub ipv4: Vec<String>,
+    pub ipv6: Vec<String>,
+    pub speed_mbps: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct BoardModelInfo {
+    pub name: String,
+    pub vendor: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct OsInfo {
+    pub name: String,
+    pub version: Option<String>,
+    /// Build of rolling or image based distributions, like `rolling` or `20240801.0`.
+    pub build: Option<String>,
+    pub arch: Option<String>,
+    pub kernel: Option<String>,
+    /// When and how the kernel was built, like `#1 SMP PREEMPT_DYNAMIC Sat, 03 Aug 2024`.
+    pub kernel_build: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct ShellInfo {
+    pub name: String,
+    pub pid: u32,
+    pub path: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct TerminalInfo {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct WindowManagerInfo {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct DesktopEnvironmentInfo {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub(crate) struct HostnameInfo {
+    pub hostname: String,
+}
+
+/// Any record produced by a graph component, tagged with the component it came from.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(untagged)]
+pub(crate) enum ComponentInfo {
+    Cpu(CpuInfo),
+    SystemMemory(MemoryInfo),
+    Gpu(GpuInfo),
+    Disk(DiskInfo),
+    Battery(BatteryInfo),
+    Net(NetInfo),
+    BoardModel(BoardModelInfo),
+    OperatingSystem(OsInfo),
+    CurrentShell(ShellInfo),
+    TerminalEmulator(TerminalInfo),
+    WindowManager(WindowManagerInfo),
+    DesktopEnvironment(DesktopEnvironmentInfo),
+}
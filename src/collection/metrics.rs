@@ -0,0 +1,305 @@
+//! Volatile readings refreshed in `--watch` mode, on top of the static facts collectors gather.
+
+use std::time::Duration;
+
+use super::context::CollectionContext;
+
+/// One sample of everything that changes while we're running, `None` where it's unavailable.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Metrics {
+    /// Memory in use and the total it's out of, in bytes.
+    pub memory: Option<(u64, u64)>,
+    /// Same for swap, `None` without any swap set up.
+    pub swap: Option<(u64, u64)>,
+    /// Load averages over 1, 5 and 15 minutes.
+    pub load_average: Option<[f64; 3]>,
+    pub uptime: Option<Duration>,
+    /// CPU usage since the previous sample, in percent across all cores.
+    pub cpu_usage: Option<f32>,
+    /// Same for each core, empty when `cpu_usage` is `None`.
+    pub core_usage: Vec<f32>,
+    pub cpu_temperature: Option<f32>,
+}
+
+/// Takes [`Metrics`] samples, remembering what it needs to compute usage between them.
+pub(crate) struct MetricsSampler {
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    ctx: CollectionContext,
+    /// (busy, total) jiffies from `/proc/stat` at the previous sample, all cores then each one.
+    #[cfg(target_os = "linux")]
+    last_cpu_times: Vec<(u64, u64)>,
+    #[cfg(not(target_os = "linux"))]
+    system: sysinfo::System,
+    #[cfg(not(target_os = "linux"))]
+    components: sysinfo::Components,
+}
+
+#[cfg(target_os = "linux")]
+impl MetricsSampler {
+    /// hwmon drivers that report the CPU package temperature as `temp1`.
+    const CPU_HWMONS: &'static [&'static str] = &[
+        "coretemp",
+        "k10temp",
+        "zenpower",
+        "cpu_thermal",
+        "cpu-thermal",
+    ];
+    /// Thermal zones that measure the CPU, used when there's no matching hwmon.
+    const CPU_THERMAL_ZONES: &'static [&'static str] =
+        &["x86_pkg_temp", "cpu-thermal", "cpu_thermal", "soc_thermal"];
+
+    pub fn new(ctx: CollectionContext) -> Self {
+        Self {
+            ctx,
+            last_cpu_times: vec![],
+        }
+    }
+
+    pub fn sample(&mut self) -> Metrics {
+        let meminfo = self.ctx.read_to_string("/proc/meminfo").unwrap_or_default();
+        let mut cpu_usage = self.cpu_usage().into_iter();
+
+        Metrics {
+            memory: meminfo_usage(&meminfo, "MemTotal", "MemAvailable"),
+            swap: meminfo_usage(&meminfo, "SwapTotal", "SwapFree"),
+            load_average: self.load_average(),
+            uptime: self.uptime(),
+            cpu_usage: cpu_usage.next(),
+            core_usage: cpu_usage.collect(),
+            cpu_temperature: self.cpu_temperature(),
+        }
+    }
+
+    fn load_average(&self) -> Option<[f64; 3]> {
+        let loadavg = self.ctx.read_to_string("/proc/loadavg").ok()?;
+        let mut fields = loadavg.split_whitespace().map(|field| field.parse().ok());
+        Some([fields.next()??, fields.next()??, fields.next()??])
+    }
+
+    fn uptime(&self) -> Option<Duration> {
+        let uptime = self.ctx.read_to_string("/proc/uptime").ok()?;
+        let seconds: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+        Some(Duration::from_secs_f64(seconds))
+    }
+
+    /// Usage across all cores followed by each core's, empty on the first sample.
+    fn cpu_usage(&mut self) -> Vec<f32> {
+        let stat = self.ctx.read_to_string("/proc/stat").unwrap_or_default();
+        let times: Vec<_> = stat
+            .lines()
+            .filter(|line| line.starts_with("cpu"))
+            .filter_map(|line| {
+                let times: Vec<u64> = line
+                    .split_whitespace()
+                    .skip(1)
+                    .filter_map(|field| field.parse().ok())
+                    .collect();
+                // user nice system idle iowait irq softirq steal, guest time is already in user
+                let total: u64 = times.iter().take(8).sum();
+                let idle = times.get(3)? + times.get(4).unwrap_or(&0);
+                Some((total.saturating_sub(idle), total))
+            })
+            .collect();
+
+        let last_times = std::mem::replace(&mut self.last_cpu_times, times);
+        if last_times.len() != self.last_cpu_times.len() {
+            return vec![];
+        }
+        self.last_cpu_times
+            .iter()
+            .zip(last_times)
+            .map(|((busy, total), (last_busy, last_total))| {
+                let elapsed = total.saturating_sub(last_total).max(1);
+                busy.saturating_sub(last_busy) as f32 * 100.0 / elapsed as f32
+            })
+            .collect()
+    }
+
+    fn cpu_temperature(&self) -> Option<f32> {
+        let millidegrees = |path: std::path::PathBuf| {
+            let temp = std::fs::read_to_string(path).ok()?;
+            temp.trim().parse::<f32>().ok().map(|temp| temp / 1000.0)
+        };
+        let entries = |dir: &str| {
+            let mut entries: Vec<_> = self
+                .ctx
+                .path(dir)
+                .read_dir()
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .collect();
+            entries.sort();
+            entries
+        };
+        let name = |path: &std::path::Path, file: &str| {
+            std::fs::read_to_string(path.join(file))
+                .ok()
+                .map(|name| name.trim().to_string())
+        };
+
+        let from_hwmon = || {
+            entries("/sys/class/hwmon").into_iter().find_map(|hwmon| {
+                let driver = name(&hwmon, "name")?;
+                Self::CPU_HWMONS
+                    .contains(&driver.as_str())
+                    .then(|| millidegrees(hwmon.join("temp1_input")))?
+            })
+        };
+        let from_thermal_zone = || {
+            entries("/sys/class/thermal")
+                .into_iter()
+                .filter(|zone| {
+                    zone.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.starts_with("thermal_zone"))
+                })
+                .find_map(|zone| {
+                    let kind = name(&zone, "type")?;
+                    Self::CPU_THERMAL_ZONES
+                        .contains(&kind.as_str())
+                        .then(|| millidegrees(zone.join("temp")))?
+                })
+        };
+
+        from_hwmon().or_else(from_thermal_zone)
+    }
+}
+
+/// (used, total) bytes from two `/proc/meminfo` fields, the total and what's left of it.
+#[cfg(target_os = "linux")]
+fn meminfo_usage(meminfo: &str, total: &str, free: &str) -> Option<(u64, u64)> {
+    let field = |name: &str| {
+        meminfo.lines().find_map(|line| {
+            let value = line.strip_prefix(name)?.strip_prefix(':')?;
+            value.split_whitespace().next()?.parse::<u64>().ok()
+        })
+    };
+
+    let total = field(total).filter(|total| *total > 0)?;
+    let free = field(free)?;
+    Some((total.saturating_sub(free) * 1024, total * 1024))
+}
+
+#[cfg(not(target_os = "linux"))]
+impl MetricsSampler {
+    /// Component labels that measure the CPU as a whole.
+    const CPU_LABELS: &'static [&'static str] = &["CPU", "Package", "Tctl", "Tdie"];
+
+    pub fn new(ctx: CollectionContext) -> Self {
+        Self {
+            ctx,
+            system: sysinfo::System::new(),
+            components: sysinfo::Components::new_with_refreshed_list(),
+        }
+    }
+
+    pub fn sample(&mut self) -> Metrics {
+        // the first refresh has nothing to compare against, like on linux
+        let first_sample = self.system.cpus().is_empty();
+        self.system.refresh_cpu_usage();
+        let cpu_usage = (!first_sample).then(|| self.system.global_cpu_usage());
+        let core_usage = match cpu_usage {
+            Some(_) => self
+                .system
+                .cpus()
+                .iter()
+                .map(sysinfo::Cpu::cpu_usage)
+                .collect(),
+            None => vec![],
+        };
+        self.system.refresh_memory();
+        self.components.refresh();
+
+        let load = sysinfo::System::load_average();
+        let cpu_temperature = self
+            .components
+            .list()
+            .iter()
+            .find(|component| {
+                Self::CPU_LABELS
+                    .iter()
+                    .any(|label| component.label().contains(label))
+            })
+            .map(|component| component.temperature())
+            .filter(|temp| temp.is_finite() && *temp > 0.0);
+
+        Metrics {
+            memory: Some((self.system.used_memory(), self.system.total_memory()))
+                .filter(|(_, total)| *total > 0),
+            swap: Some((self.system.used_swap(), self.system.total_swap()))
+                .filter(|(_, total)| *total > 0),
+            // windows has no load average and reports zeroes
+            load_average: Some([load.one, load.five, load.fifteen])
+                .filter(|load| load.iter().any(|avg| *avg > 0.0)),
+            uptime: Some(Duration::from_secs(sysinfo::System::uptime())),
+            cpu_usage,
+            core_usage,
+            cpu_temperature,
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::collection::fixtures::{fixture, offline};
+    use std::fs;
+
+    #[test]
+    fn test_sample_from_fixture() {
+        let root = fixture(&[
+            (
+                "/proc/meminfo",
+                "MemTotal: 1000 kB\nMemFree: 100 kB\nMemAvailable: 750 kB\nSwapTotal: 0 kB\n",
+            ),
+            ("/proc/loadavg", "0.52 0.58 0.59 1/467 12345\n"),
+            ("/proc/uptime", "93784.25 180000.00\n"),
+            (
+                "/proc/stat",
+                "cpu  100 0 100 700 100 0 0 0 0 0\ncpu0 50 0 50 350 50\ncpu1 50 0 50 350 50\n",
+            ),
+            ("/sys/class/hwmon/hwmon0/name", "acpitz\n"),
+            ("/sys/class/hwmon/hwmon0/temp1_input", "27800\n"),
+            ("/sys/class/hwmon/hwmon1/name", "k10temp\n"),
+            ("/sys/class/hwmon/hwmon1/temp1_input", "45125\n"),
+        ]);
+
+        let mut sampler = MetricsSampler::new(offline(&root));
+        let metrics = sampler.sample();
+        assert_eq!(metrics.memory, Some((250 * 1024, 1000 * 1024)));
+        assert_eq!(metrics.swap, None);
+        assert_eq!(metrics.load_average, Some([0.52, 0.58, 0.59]));
+        assert_eq!(metrics.uptime, Some(Duration::from_secs_f64(93784.25)));
+        assert_eq!(metrics.cpu_temperature, Some(45.125));
+        // usage needs two samples to compare
+        assert_eq!(metrics.cpu_usage, None);
+        assert!(metrics.core_usage.is_empty());
+
+        // 300 more jiffies busy out of 400, all of it on the first core
+        fs::write(
+            root.path().join("proc/stat"),
+            "cpu  250 0 250 750 150 0 0 0 0 0\ncpu0 200 0 200 350 50\ncpu1 50 0 50 400 100\n",
+        )
+        .unwrap();
+        let metrics = sampler.sample();
+        assert_eq!(metrics.cpu_usage, Some(75.0));
+        assert_eq!(metrics.core_usage, vec![100.0, 0.0]);
+    }
+
+    #[test]
+    fn test_thermal_zone_fallback() {
+        let root = fixture(&[
+            ("/sys/class/thermal/thermal_zone0/type", "acpitz\n"),
+            ("/sys/class/thermal/thermal_zone0/temp", "27800\n"),
+            ("/sys/class/thermal/thermal_zone1/type", "x86_pkg_temp\n"),
+            ("/sys/class/thermal/thermal_zone1/temp", "51000\n"),
+        ]);
+
+        let mut sampler = MetricsSampler::new(offline(&root));
+        let metrics = sampler.sample();
+        assert_eq!(metrics.cpu_temperature, Some(51.0));
+        assert_eq!(metrics.memory, None);
+    }
+}
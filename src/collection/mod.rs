@@ -1,21 +1,41 @@
 use anyhow::Result;
-use std::{cmp::max, collections::HashMap};
+use std::{cmp::max, collections::HashMap, time::Duration};
 use strum::IntoEnumIterator;
 use tui_nodes::Connection;
+use unicode_width::UnicodeWidthStr;
 
-use crate::args::VisualToggles;
+use crate::config::Config;
 
+mod concurrent;
+mod context;
+pub(crate) mod diagnostics;
+mod format;
+pub(crate) mod info;
+pub(crate) mod metrics;
 pub(crate) mod system_components;
+pub(crate) mod topology;
+pub(crate) use concurrent::TimedOut;
+#[cfg(test)]
+pub(crate) use context::fixtures;
+pub(crate) use context::CollectionContext;
+use info::{ComponentInfo, HostnameInfo};
+use metrics::Metrics;
 use system_components::{
-    BoardModel, Cpu, CurrentShell, DesktopEnvironment, Gpu, OperatingSystem, SystemComponent,
-    SystemMemory, TerminalEmulator, WindowManager,
+    Battery, BoardModel, Cpu, CurrentShell, DesktopEnvironment, Disk, Gpu, Hostname, Net,
+    OperatingSystem, SystemComponent, SystemMemory, TerminalEmulator, WindowManager,
 };
 
+/// How long a collector gets before its component is shown as timed out.
+const COLLECTOR_TIMEOUT: Duration = Duration::from_secs(1);
+
 #[derive(strum::EnumIter, Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
-enum SystemComponentKind {
+pub(crate) enum SystemComponentKind {
     Cpu,
     SystemMemory,
     Gpu,
+    Disk,
+    Battery,
+    Net,
     BoardModel,
     OperatingSystem,
     CurrentShell,
@@ -25,11 +45,35 @@ enum SystemComponentKind {
 }
 
 impl SystemComponentKind {
+    /// Stable identifier used in machine-readable output.
+    pub fn id(&self) -> &'static str {
+        match self {
+            SystemComponentKind::Cpu => "cpu",
+            SystemComponentKind::SystemMemory => "system_memory",
+            SystemComponentKind::Gpu => "gpu",
+            SystemComponentKind::Disk => "disk",
+            SystemComponentKind::Battery => "battery",
+            SystemComponentKind::Net => "net",
+            SystemComponentKind::BoardModel => "board_model",
+            SystemComponentKind::OperatingSystem => "operating_system",
+            SystemComponentKind::CurrentShell => "current_shell",
+            SystemComponentKind::TerminalEmulator => "terminal_emulator",
+            SystemComponentKind::WindowManager => "window_manager",
+            SystemComponentKind::DesktopEnvironment => "desktop_environment",
+        }
+    }
+    /// Inverse of [`SystemComponentKind::id`].
+    pub fn from_id(id: &str) -> Option<Self> {
+        SystemComponentKind::iter().find(|kind| kind.id() == id)
+    }
     pub fn title(&self) -> &'static str {
         match self {
             SystemComponentKind::Cpu => "[ CPU ]",
             SystemComponentKind::SystemMemory => "[ RAM ]",
             SystemComponentKind::Gpu => "[ GPU ]",
+            SystemComponentKind::Disk => "[ Disk ]",
+            SystemComponentKind::Battery => "[ Battery ]",
+            SystemComponentKind::Net => "[ Net ]",
             SystemComponentKind::BoardModel => "[ Model ]",
             SystemComponentKind::OperatingSystem => "[ OS ]",
             SystemComponentKind::CurrentShell => "[ Shell ]",
@@ -38,32 +82,221 @@ impl SystemComponentKind {
             SystemComponentKind::WindowManager => "[ WM ]",
         }
     }
-    pub fn collect_info(&self, vt: &VisualToggles) -> Result<Vec<String>> {
+    /// How long this component's collector may run before we give up on it.
+    pub fn timeout(&self) -> Duration {
         match self {
-            SystemComponentKind::Cpu => Cpu::collect_info(vt),
-            SystemComponentKind::SystemMemory => SystemMemory::collect_info(vt),
-            SystemComponentKind::BoardModel => BoardModel::collect_info(vt),
-            SystemComponentKind::CurrentShell => CurrentShell::collect_info(vt),
-            SystemComponentKind::TerminalEmulator => TerminalEmulator::collect_info(vt),
-            SystemComponentKind::DesktopEnvironment => DesktopEnvironment::collect_info(vt),
-            SystemComponentKind::WindowManager => WindowManager::collect_info(vt),
-            SystemComponentKind::OperatingSystem => OperatingSystem::collect_info(vt),
-            SystemComponentKind::Gpu => Gpu::collect_info(vt),
+            // parsing pci.ids takes a while on slow disks
+            SystemComponentKind::Gpu => COLLECTOR_TIMEOUT * 2,
+            _ => COLLECTOR_TIMEOUT,
         }
     }
+    pub fn collect_info(&self, ctx: &CollectionContext) -> Result<Vec<ComponentInfo>> {
+        match self {
+            SystemComponentKind::Cpu => wrap(Cpu::collect_info(ctx), ComponentInfo::Cpu),
+            SystemComponentKind::SystemMemory => {
+                wrap(SystemMemory::collect_info(ctx), ComponentInfo::SystemMemory)
+            }
+            SystemComponentKind::BoardModel => {
+                wrap(BoardModel::collect_info(ctx), ComponentInfo::BoardModel)
+            }
+            SystemComponentKind::CurrentShell => {
+                wrap(CurrentShell::collect_info(ctx), ComponentInfo::CurrentShell)
+            }
+            SystemComponentKind::TerminalEmulator => wrap(
+                TerminalEmulator::collect_info(ctx),
+                ComponentInfo::TerminalEmulator,
+            ),
+            SystemComponentKind::DesktopEnvironment => wrap(
+                DesktopEnvironment::collect_info(ctx),
+                ComponentInfo::DesktopEnvironment,
+            ),
+            SystemComponentKind::WindowManager => wrap(
+                WindowManager::collect_info(ctx),
+                ComponentInfo::WindowManager,
+            ),
+            SystemComponentKind::OperatingSystem => wrap(
+                OperatingSystem::collect_info(ctx),
+                ComponentInfo::OperatingSystem,
+            ),
+            SystemComponentKind::Gpu => wrap(Gpu::collect_info(ctx), ComponentInfo::Gpu),
+            SystemComponentKind::Disk => wrap(Disk::collect_info(ctx), ComponentInfo::Disk),
+            SystemComponentKind::Battery => {
+                wrap(Battery::collect_info(ctx), ComponentInfo::Battery)
+            }
+            SystemComponentKind::Net => wrap(Net::collect_info(ctx), ComponentInfo::Net),
+        }
+    }
+}
+
+impl serde::Serialize for SystemComponentKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+impl<'de> serde::Deserialize<'de> for SystemComponentKind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        SystemComponentKind::from_id(&id)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown component `{id}`")))
+    }
+}
+
+/// Tags every record of a collector's output with its [`ComponentInfo`] variant.
+fn wrap<T>(info: Result<Vec<T>>, variant: fn(T) -> ComponentInfo) -> Result<Vec<ComponentInfo>> {
+    info.map(|records| records.into_iter().map(variant).collect())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct CollectedNode {
     pub width: u16,
     pub height: u16,
-    pub title: &'static str,
+    pub title: String,
     pub body: String,
+    /// What the node shows, `None` for placeholders of components that couldn't be collected.
+    pub info: Option<ComponentInfo>,
+    /// Live readings shown after the info in `--watch` mode.
+    pub readings: Option<String>,
+    /// Number of (outgoing, incoming) connection ports.
+    pub ports: (usize, usize),
 }
 
+impl CollectedNode {
+    pub fn new(title: impl Into<String>, info: ComponentInfo, ports: (usize, usize)) -> Self {
+        Self::with_body(title, info.to_string(), Some(info), None, ports)
+    }
+
+    /// Node standing in for a component that couldn't be collected, showing `message` instead.
+    pub fn unavailable(title: impl Into<String>, message: &str, ports: (usize, usize)) -> Self {
+        Self::with_body(title, message.to_string(), None, None, ports)
+    }
+
+    /// Same node with a shortened body, for when space is tight.
+    pub fn compact(&self) -> Self {
+        match &self.info {
+            Some(info) => Self::with_body(
+                self.title.clone(),
+                info.compact_summary(),
+                self.info.clone(),
+                self.readings.clone(),
+                self.ports,
+            ),
+            None => self.clone(),
+        }
+    }
+
+    /// Same node showing whatever in `metrics` is about its component.
+    pub fn with_metrics(&self, metrics: &Metrics) -> Self {
+        match &self.info {
+            Some(info) => Self::with_body(
+                self.title.clone(),
+                info.to_string(),
+                self.info.clone(),
+                metrics.describe(info),
+                self.ports,
+            ),
+            None => self.clone(),
+        }
+    }
+
+    fn with_body(
+        title: impl Into<String>,
+        text: String,
+        info: Option<ComponentInfo>,
+        readings: Option<String>,
+        ports: (usize, usize),
+    ) -> Self {
+        let title = title.into();
+        let text = match &readings {
+            Some(readings) => format!("{text} · {readings}"),
+            None => text,
+        };
+        // lines are padded to the same width, so they line up rather than being centered
+        let body_width = text.lines().map(|line| line.width()).max().unwrap_or(0);
+        let lines: Vec<_> = text
+            .lines()
+            .map(|line| format!(" {line}{} ", " ".repeat(body_width - line.width())))
+            .collect();
+        let body = lines.join("\n");
+        // 2 is the box's borders and 2 the padding, we make sure we can fit either the title
+        // or body (or both)
+        let width = max(title.width(), body_width + 2) + 2;
+        // same thing with either of the box's sides, always leaving a row for each body line
+        let height = max(max(ports.0, ports.1), max(lines.len(), 1)) + 2;
+
+        Self {
+            width: width as u16,
+            height: height as u16,
+            title,
+            body,
+            info,
+            readings,
+            ports,
+        }
+    }
+}
+
+/// Outcome of running one component's collector.
+#[derive(Debug)]
+pub(crate) struct ComponentReport {
+    pub kind: SystemComponentKind,
+    /// The component's records or the error it failed with, [`TimedOut`] when it ran past
+    /// its [`SystemComponentKind::timeout`].
+    pub result: Result<Vec<ComponentInfo>>,
+    pub elapsed: Duration,
+}
+
+/// Hostname for the frame around the graph, `unknown` when there's none to be found, like on
+/// images without `/etc/hostname`. `--diagnose` and `--json` report why.
+pub(crate) fn hostname(ctx: &CollectionContext) -> HostnameInfo {
+    match Hostname::collect_info(ctx).map(|hostname| hostname.into_iter().next()) {
+        Ok(Some(hostname)) => hostname,
+        result => {
+            tracing::debug!("no hostname: {:?}", result.err());
+            HostnameInfo {
+                hostname: "unknown".to_string(),
+            }
+        }
+    }
+}
+
+/// Runs the collectors for `kinds` concurrently, reporting on them in the same order.
+pub(crate) fn collect_components(
+    ctx: &CollectionContext,
+    kinds: &[SystemComponentKind],
+) -> Vec<ComponentReport> {
+    let jobs = kinds
+        .iter()
+        .copied()
+        .map(|kind| {
+            let ctx = ctx.clone();
+            let job: concurrent::Job<_> = Box::new(move || kind.collect_info(&ctx));
+            (kind.id(), kind.timeout(), job)
+        })
+        .collect();
+
+    kinds
+        .iter()
+        .copied()
+        .zip(concurrent::run_all(jobs))
+        .map(|(kind, (result, elapsed))| ComponentReport {
+            kind,
+            result,
+            elapsed,
+        })
+        .collect()
+}
+
+/// Turns collected components into the graph's nodes and the links between them.
+///
+/// Components that timed out always get a placeholder node so it's clear something is
+/// missing, ones that failed only do with `show_unknown`.
 pub(crate) fn collect(
-    visual_toggles: VisualToggles,
+    reports: &[ComponentReport],
+    config: &Config,
 ) -> Result<(Vec<CollectedNode>, Vec<Connection>)> {
+    let topology = config.topology()?;
+
     // accumulator to generate component ids
     let mut component_id_acc: usize = 0;
 
@@ -71,25 +304,27 @@ pub(crate) fn collect(
     let mut ports: HashMap<usize, (usize, usize)> =
         HashMap::with_capacity(SystemComponentKind::iter().len());
 
-    let components: Vec<_> = SystemComponentKind::iter()
-        // collect info
-        .map(|k| (k, k.collect_info(&visual_toggles)))
-        .flat_map(|(kind, component_info_outer)| match component_info_outer {
-            // if component is disabled or cant be displayed
-            Err(_) => vec![(0, kind, None)],
-            // otherwise
+    let components: Vec<_> = reports
+        .iter()
+        .flat_map(|report| match &report.result {
+            // still show where a slow component would have been
+            Err(e) if e.is::<TimedOut>() => vec![(report.kind, Err("timed out"))],
+            Err(_) if config.toggles.show_unknown => vec![(report.kind, Err("unknown"))],
+            // the error is kept in the report for diagnostics
+            Err(_) => vec![],
             Ok(component_info) => component_info
-                .into_iter()
-                .map(|info_string| {
-                    // get an id
-                    let component_id = component_id_acc;
-                    component_id_acc += 1;
-                    // prepare ports entry
-                    ports.insert(component_id, (0, 0));
-                    (component_id, kind, Some(format!(" {info_string} ")))
-                })
+                .iter()
+                .map(|info| (report.kind, Ok(info.clone())))
                 .collect(),
         })
+        .map(|(kind, info)| {
+            // get an id
+            let component_id = component_id_acc;
+            component_id_acc += 1;
+            // prepare ports entry
+            ports.insert(component_id, (0, 0));
+            (component_id, kind, info)
+        })
         .collect();
 
     let links: Vec<_> = components
@@ -98,24 +333,7 @@ pub(crate) fn collect(
             components
                 .iter()
                 // we get the linked component(s)
-                .filter(|component| match kind {
-                    SystemComponentKind::SystemMemory | SystemComponentKind::Cpu => {
-                        component.1 == SystemComponentKind::BoardModel
-                    }
-                    SystemComponentKind::BoardModel => {
-                        component.1 == SystemComponentKind::OperatingSystem
-                    }
-                    SystemComponentKind::OperatingSystem => matches!(
-                        component.1,
-                        SystemComponentKind::TerminalEmulator
-                            | SystemComponentKind::WindowManager
-                            | SystemComponentKind::DesktopEnvironment
-                    ),
-                    SystemComponentKind::TerminalEmulator => {
-                        component.1 == SystemComponentKind::CurrentShell
-                    }
-                    _ => false,
-                })
+                .filter(|component| topology.connects(*kind, component.1))
                 .map(|dst_component| {
                     // these unwraps should be fine :3
                     let src_port = ports
@@ -142,23 +360,9 @@ pub(crate) fn collect(
 
     let components: Vec<CollectedNode> = components
         .into_iter()
-        // TODO: properly handle missing components
-        .filter(|(_, _, i)| i.is_some())
-        .map(|(id, kind, info)| {
-            let title = kind.title();
-            let body = info.unwrap();
-            let ports = ports[&id];
-            // 2 is the box's borders, we make sure we can fit either the title or body (or both)
-            let width = (max(title.len(), body.len())) + 2;
-            // same thing with either of the box's sides
-            let height = max(ports.0, ports.1) + 2;
-
-            CollectedNode {
-                width: width as u16,
-                height: height as u16,
-                title,
-                body,
-            }
+        .map(|(id, kind, info)| match info {
+            Ok(info) => CollectedNode::new(config.title(kind), info, ports[&id]),
+            Err(message) => CollectedNode::unavailable(config.title(kind), message, ports[&id]),
         })
         .collect();
 
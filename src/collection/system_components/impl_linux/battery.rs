@@ -0,0 +1,181 @@
+use anyhow::Error;
+use std::path::Path;
+
+use super::{read, sorted_entries};
+use crate::collection::context::CollectionContext;
+use crate::collection::info::BatteryInfo;
+use crate::collection::system_components::{Battery, SystemComponent};
+
+impl SystemComponent for Battery {
+    type Info = BatteryInfo;
+
+    /// Desktops have no batteries, and get no node rather than an error.
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let class = ctx.path("/sys/class/power_supply");
+        let supplies: Vec<_> = sorted_entries(&class)?
+            .into_iter()
+            .map(|name| {
+                let kind = read(&class.join(&name).join("type"));
+                (name, kind)
+            })
+            .collect();
+        // any adapter that's plugged in powers the machine
+        let adapters: Vec<_> = supplies
+            .iter()
+            .filter(|(_, kind)| kind.as_deref() == Some("Mains"))
+            .map(|(name, _)| read(&class.join(name).join("online")).as_deref() == Some("1"))
+            .collect();
+        let ac_online = (!adapters.is_empty()).then(|| adapters.contains(&true));
+
+        let batteries = supplies
+            .iter()
+            .filter(|(_, kind)| kind.as_deref() == Some("Battery"))
+            .map(|(name, _)| (name, class.join(name)))
+            // mice, keyboards and headsets report their batteries here too
+            .filter(|(_, dir)| read(&dir.join("scope")).as_deref() != Some("Device"))
+            .map(|(name, dir)| battery(name, &dir, ac_online))
+            .collect();
+        Ok(batteries)
+    }
+}
+
+fn battery(name: &str, dir: &Path, ac_online: Option<bool>) -> BatteryInfo {
+    let number = |file: &str| read(&dir.join(file)).and_then(|value| value.parse::<u64>().ok());
+    // drivers report either energy in µWh or charge in µAh, the ratios come out the same
+    let ratio = |now: &str, full: &str| {
+        let (now, full) = (number(now)?, number(full)?);
+        (now * 100 + full / 2).checked_div(full)
+    };
+    let percent = |value: u64| u8::try_from(value).ok();
+
+    BatteryInfo {
+        name: name.to_string(),
+        model: read(&dir.join("model_name")),
+        vendor: read(&dir.join("manufacturer")),
+        charge_percent: number("capacity")
+            .or_else(|| ratio("energy_now", "energy_full"))
+            .or_else(|| ratio("charge_now", "charge_full"))
+            .and_then(percent),
+        state: read(&dir.join("status"))
+            .map(|status| status.to_lowercase())
+            .unwrap_or_else(|| "unknown".to_string()),
+        health_percent: ratio("energy_full", "energy_full_design")
+            .or_else(|| ratio("charge_full", "charge_full_design"))
+            .and_then(percent),
+        // batteries that don't count cycles say 0
+        cycles: number("cycle_count")
+            .filter(|cycles| *cycles > 0)
+            .and_then(|cycles| u32::try_from(cycles).ok()),
+        ac_online,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::context::fixtures::{fixture, offline};
+    use testresult::TestResult;
+
+    #[test]
+    fn test_laptop_batteries() -> TestResult {
+        let root = fixture(&[
+            ("/sys/class/power_supply/AC/type", "Mains\n"),
+            ("/sys/class/power_supply/AC/online", "1\n"),
+            ("/sys/class/power_supply/BAT0/type", "Battery\n"),
+            ("/sys/class/power_supply/BAT0/status", "Charging\n"),
+            ("/sys/class/power_supply/BAT0/capacity", "87\n"),
+            ("/sys/class/power_supply/BAT0/energy_now", "44000000\n"),
+            ("/sys/class/power_supply/BAT0/energy_full", "50000000\n"),
+            (
+                "/sys/class/power_supply/BAT0/energy_full_design",
+                "57000000\n",
+            ),
+            ("/sys/class/power_supply/BAT0/cycle_count", "312\n"),
+            ("/sys/class/power_supply/BAT0/model_name", "5B10W13930\n"),
+            ("/sys/class/power_supply/BAT0/manufacturer", "SMP\n"),
+            // no capacity file, the charge is worked out from what it holds
+            ("/sys/class/power_supply/BAT1/type", "Battery\n"),
+            ("/sys/class/power_supply/BAT1/status", "Not charging\n"),
+            ("/sys/class/power_supply/BAT1/charge_now", "1500000\n"),
+            ("/sys/class/power_supply/BAT1/charge_full", "2000000\n"),
+            (
+                "/sys/class/power_supply/BAT1/charge_full_design",
+                "2000000\n",
+            ),
+            ("/sys/class/power_supply/BAT1/cycle_count", "0\n"),
+            // a wireless mouse
+            ("/sys/class/power_supply/hidpp_battery_0/type", "Battery\n"),
+            ("/sys/class/power_supply/hidpp_battery_0/scope", "Device\n"),
+            ("/sys/class/power_supply/hidpp_battery_0/capacity", "40\n"),
+            (
+                "/sys/class/power_supply/ucsi-source-psy-USBC000:001/type",
+                "USB\n",
+            ),
+        ]);
+
+        assert_eq!(
+            Battery::collect_info(&offline(&root))?,
+            vec![
+                BatteryInfo {
+                    name: "BAT0".to_string(),
+                    model: Some("5B10W13930".to_string()),
+                    vendor: Some("SMP".to_string()),
+                    charge_percent: Some(87),
+                    state: "charging".to_string(),
+                    health_percent: Some(88),
+                    cycles: Some(312),
+                    ac_online: Some(true),
+                },
+                BatteryInfo {
+                    name: "BAT1".to_string(),
+                    model: None,
+                    vendor: None,
+                    charge_percent: Some(75),
+                    state: "not charging".to_string(),
+                    health_percent: Some(100),
+                    cycles: None,
+                    ac_online: Some(true),
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unplugged_without_details() -> TestResult {
+        let root = fixture(&[
+            ("/sys/class/power_supply/ADP1/type", "Mains\n"),
+            ("/sys/class/power_supply/ADP1/online", "0\n"),
+            ("/sys/class/power_supply/BAT0/type", "Battery\n"),
+        ]);
+
+        assert_eq!(
+            Battery::collect_info(&offline(&root))?,
+            vec![BatteryInfo {
+                name: "BAT0".to_string(),
+                model: None,
+                vendor: None,
+                charge_percent: None,
+                state: "unknown".to_string(),
+                health_percent: None,
+                cycles: None,
+                ac_online: Some(false),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_desktop_has_no_battery() -> TestResult {
+        let root = fixture(&[]);
+        assert_eq!(Battery::collect_info(&offline(&root))?, vec![]);
+
+        // an adapter alone doesn't make a laptop
+        let root = fixture(&[
+            ("/sys/class/power_supply/AC/type", "Mains\n"),
+            ("/sys/class/power_supply/AC/online", "1\n"),
+        ]);
+        assert_eq!(Battery::collect_info(&offline(&root))?, vec![]);
+        Ok(())
+    }
+}
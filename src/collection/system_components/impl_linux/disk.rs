@@ -0,0 +1,351 @@
+use anyhow::Error;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use super::{read, sorted_entries};
+use crate::collection::context::CollectionContext;
+use crate::collection::info::{DiskInfo, DiskKind, Drive, Filesystem};
+use crate::collection::system_components::{Disk, SystemComponent};
+
+/// Bytes in the sectors `/sys/block` counts sizes in, whatever the disk's own sector size.
+const SECTOR_SIZE: u64 = 512;
+
+impl SystemComponent for Disk {
+    type Info = DiskInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let mounts = ctx.read_to_string("/proc/mounts").unwrap_or_default();
+        // only the running system's filesystems can be asked how full they are
+        let usage = if ctx.is_live() {
+            live_usage()
+        } else {
+            HashMap::new()
+        };
+
+        let disks = collect_disks(ctx, &mounts, &usage)?;
+        if disks.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![DiskInfo { disks }])
+    }
+}
+
+/// Used and total bytes of each mounted filesystem, by mount point.
+fn live_usage() -> HashMap<String, (u64, u64)> {
+    sysinfo::Disks::new_with_refreshed_list()
+        .iter()
+        .map(|disk| {
+            let total = disk.total_space();
+            // what's free for anyone, like `df` leaving out the blocks kept for root
+            let used = total.saturating_sub(disk.available_space());
+            (
+                disk.mount_point().to_string_lossy().into_owned(),
+                (used, total),
+            )
+        })
+        .collect()
+}
+
+/// Lists the physical disks in `/sys/block` with the filesystems in `mounts` that are on them.
+fn collect_disks(
+    ctx: &CollectionContext,
+    mounts: &str,
+    usage: &HashMap<String, (u64, u64)>,
+) -> Result<Vec<Drive>, Error> {
+    let block = ctx.path("/sys/block");
+
+    let mut disks = vec![];
+    for name in sorted_entries(&block)? {
+        let dir = block.join(&name);
+        // loop, zram, device mapper and RAID devices have no hardware of their own
+        if !dir.join("device").exists() {
+            continue;
+        }
+        let size_bytes = read(&dir.join("size"))
+            .and_then(|sectors| sectors.parse::<u64>().ok())
+            .unwrap_or(0)
+            * SECTOR_SIZE;
+        // card readers and optical drives with nothing in them
+        if size_bytes == 0 {
+            continue;
+        }
+
+        disks.push(Drive {
+            device: format!("/dev/{name}"),
+            model: read(&dir.join("device/model")),
+            size_bytes,
+            kind: disk_kind(&name, &dir),
+            removable: read(&dir.join("removable")).as_deref() == Some("1"),
+            filesystems: vec![],
+        });
+    }
+
+    // pseudo and network filesystems aren't on any disk, so they're left out here too
+    let mut seen = HashSet::new();
+    for (source, mount_point, fs_type) in parse_mounts(mounts) {
+        // bind mounts and btrfs subvolumes show the same filesystem again
+        if !source.starts_with("/dev/") || !seen.insert(source.clone()) {
+            continue;
+        }
+        let Some(disk) = device_name(ctx, &source)
+            .and_then(|name| disk_of(&block, &name))
+            .and_then(|name| {
+                let device = format!("/dev/{name}");
+                disks.iter_mut().find(|disk| disk.device == device)
+            })
+        else {
+            continue;
+        };
+
+        let usage = usage.get(&mount_point);
+        disk.filesystems.push(Filesystem {
+            source,
+            mount_point,
+            fs_type,
+            size_bytes: usage.map(|(_, total)| *total),
+            used_bytes: usage.map(|(used, _)| *used),
+        });
+    }
+
+    Ok(disks)
+}
+
+fn disk_kind(name: &str, dir: &Path) -> Option<DiskKind> {
+    if is_virtual(dir) {
+        return Some(DiskKind::Virtual);
+    }
+    if name.starts_with("nvme") {
+        return Some(DiskKind::Nvme);
+    }
+    match read(&dir.join("queue/rotational")).as_deref() {
+        Some("1") => Some(DiskKind::Hdd),
+        Some("0") => Some(DiskKind::Ssd),
+        _ => None,
+    }
+}
+
+/// Whether the disk reaches us through virtio or Xen's blkfront, which leave
+/// `queue/rotational` at 1 whatever the host keeps the disk on.
+fn is_virtual(dir: &Path) -> bool {
+    let Ok(device) = fs::canonicalize(dir.join("device")) else {
+        return false;
+    };
+    device.components().any(|part| {
+        let part = part.as_os_str().to_string_lossy();
+        part.starts_with("virtio") || part.starts_with("vbd-")
+    })
+}
+
+/// The source, mount point and type of each line of `/proc/mounts`.
+fn parse_mounts(mounts: &str) -> Vec<(String, String, String)> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().map(unescape);
+            Some((fields.next()?, fields.next()?, fields.next()?))
+        })
+        .collect()
+}
+
+/// Undoes the octal escapes `/proc/mounts` uses for spaces and the like, as in `\040`.
+fn unescape(field: &str) -> String {
+    let mut out = String::new();
+    let mut rest = field;
+    while let Some(start) = rest.find('\\') {
+        out.push_str(&rest[..start]);
+        let code = rest
+            .get(start + 1..start + 4)
+            .and_then(|code| u8::from_str_radix(code, 8).ok());
+        match code {
+            Some(code) => {
+                out.push(code as char);
+                rest = &rest[start + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Kernel name of the block device at `source`, following links like `/dev/mapper/root`
+/// to `dm-0`.
+fn device_name(ctx: &CollectionContext, source: &str) -> Option<String> {
+    let path = ctx.path(source);
+    let path = fs::canonicalize(&path).unwrap_or(path);
+    Some(path.file_name()?.to_string_lossy().into_owned())
+}
+
+/// The disk device `name` is, is a partition of, or is built on through device mapper or
+/// RAID, the first of them for devices spanning several.
+fn disk_of(block: &Path, name: &str) -> Option<String> {
+    let dir = block.join(name);
+    if dir.is_dir() {
+        let slave = sorted_entries(&dir.join("slaves"))
+            .ok()
+            .and_then(|slaves| slaves.into_iter().next());
+        return match slave {
+            Some(slave) => disk_of(block, &slave),
+            None => Some(name.to_string()),
+        };
+    }
+
+    // partitions are listed inside their disk's directory
+    fs::read_dir(block)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .find(|disk| block.join(disk).join(name).is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::context::fixtures::{fixture, offline};
+    use testresult::TestResult;
+
+    const BLOCK_DEVICES: &[(&str, &str)] = &[
+        ("/sys/block/nvme0n1/size", "2000000\n"),
+        ("/sys/block/nvme0n1/removable", "0\n"),
+        ("/sys/block/nvme0n1/queue/rotational", "0\n"),
+        ("/sys/block/nvme0n1/device/model", "Samsung SSD 980 PRO\n"),
+        ("/sys/block/nvme0n1/nvme0n1p1/partition", "1\n"),
+        ("/sys/block/nvme0n1/nvme0n1p2/partition", "2\n"),
+        ("/sys/block/sda/size", "4000000\n"),
+        ("/sys/block/sda/removable", "0\n"),
+        ("/sys/block/sda/queue/rotational", "1\n"),
+        ("/sys/block/sda/device/model", "WDC WD40EFRX\n"),
+        ("/sys/block/sda/sda1/partition", "1\n"),
+        // an empty card reader
+        ("/sys/block/sdb/size", "0\n"),
+        ("/sys/block/sdb/removable", "1\n"),
+        ("/sys/block/sdb/device/model", "Card Reader\n"),
+        // virtual devices, without hardware behind them
+        ("/sys/block/loop0/size", "1000\n"),
+        ("/sys/block/dm-0/size", "1000\n"),
+        ("/sys/block/dm-0/slaves/nvme0n1p2/partition", "2\n"),
+        ("/dev/dm-0", ""),
+    ];
+
+    #[test]
+    fn test_collect_disks_and_filesystems() -> TestResult {
+        let root = fixture(BLOCK_DEVICES);
+        // /dev/mapper/root is a link to the device mapper device
+        fs::create_dir_all(root.path().join("dev/mapper"))?;
+        std::os::unix::fs::symlink("../dm-0", root.path().join("dev/mapper/root"))?;
+
+        let mounts = "\
+/dev/mapper/root / btrfs rw,relatime 0 0
+proc /proc proc rw,nosuid 0 0
+tmpfs /tmp tmpfs rw 0 0
+/dev/nvme0n1p1 /boot vfat rw 0 0
+/dev/mapper/root /home btrfs rw,subvol=/home 0 0
+/dev/sda1 /mnt/my\\040disk ext4 rw 0 0
+/dev/loop0 /snap/core/1 squashfs ro 0 0
+server:/export /srv nfs rw 0 0
+";
+        let usage = HashMap::from([("/".to_string(), (40, 100))]);
+        let disks = collect_disks(&offline(&root), mounts, &usage)?;
+
+        let filesystem = |source: &str, mount_point: &str, fs_type: &str| Filesystem {
+            source: source.to_string(),
+            mount_point: mount_point.to_string(),
+            fs_type: fs_type.to_string(),
+            size_bytes: None,
+            used_bytes: None,
+        };
+        assert_eq!(
+            disks,
+            vec![
+                Drive {
+                    device: "/dev/nvme0n1".to_string(),
+                    model: Some("Samsung SSD 980 PRO".to_string()),
+                    size_bytes: 2_000_000 * 512,
+                    kind: Some(DiskKind::Nvme),
+                    removable: false,
+                    filesystems: vec![
+                        Filesystem {
+                            size_bytes: Some(100),
+                            used_bytes: Some(40),
+                            ..filesystem("/dev/mapper/root", "/", "btrfs")
+                        },
+                        filesystem("/dev/nvme0n1p1", "/boot", "vfat"),
+                    ],
+                },
+                Drive {
+                    device: "/dev/sda".to_string(),
+                    model: Some("WDC WD40EFRX".to_string()),
+                    size_bytes: 4_000_000 * 512,
+                    kind: Some(DiskKind::Hdd),
+                    removable: false,
+                    filesystems: vec![filesystem("/dev/sda1", "/mnt/my disk", "ext4")],
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_virtual_disks() -> TestResult {
+        let root = fixture(&[
+            ("/sys/block/vda/size", "536870912\n"),
+            ("/sys/block/vda/queue/rotational", "1\n"),
+            (
+                "/sys/devices/pci0000:00/0000:00:05.0/virtio2/vendor",
+                "0x1af4\n",
+            ),
+            ("/sys/block/xvda/size", "16777216\n"),
+            ("/sys/block/xvda/queue/rotational", "1\n"),
+            ("/sys/devices/vbd-51712/devtype", "vbd\n"),
+            // a disk too old to say what it is
+            ("/sys/block/sdc/size", "1000\n"),
+            ("/sys/block/sdc/device/model", "Old Disk\n"),
+        ]);
+        for (disk, device) in [
+            ("vda", "../../devices/pci0000:00/0000:00:05.0/virtio2"),
+            ("xvda", "../../devices/vbd-51712"),
+        ] {
+            std::os::unix::fs::symlink(
+                device,
+                root.path().join("sys/block").join(disk).join("device"),
+            )?;
+        }
+
+        let kinds: Vec<_> = collect_disks(&offline(&root), "", &HashMap::new())?
+            .into_iter()
+            .map(|disk| (disk.device, disk.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("/dev/sdc".to_string(), None),
+                ("/dev/vda".to_string(), Some(DiskKind::Virtual)),
+                ("/dev/xvda".to_string(), Some(DiskKind::Virtual)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_one_node_for_all_disks() -> TestResult {
+        let root = fixture(BLOCK_DEVICES);
+        let info = Disk::collect_info(&offline(&root))?;
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].disks.len(), 2);
+
+        let root = fixture(&[]);
+        assert_eq!(Disk::collect_info(&offline(&root))?, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape("/mnt/my\\040disk"), "/mnt/my disk");
+        assert_eq!(unescape("tab\\011and\\134"), "tab\tand\\");
+        assert_eq!(unescape("odd\\x"), "odd\\x");
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::Error;
+use std::fs;
+use std::path::Path;
+
+use super::{read, sorted_entries};
+use crate::collection::context::CollectionContext;
+use crate::collection::info::GpuInfo;
+use crate::collection::system_components::{Gpu, SystemComponent};
+
+/// Where distributions usually install the PCI ID database.
+const PCI_IDS_PATHS: &[&str] = &[
+    "/usr/share/hwdata/pci.ids",
+    "/usr/share/misc/pci.ids",
+    "/usr/share/pci.ids",
+];
+
+impl SystemComponent for Gpu {
+    type Info = GpuInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let pci_ids = PCI_IDS_PATHS
+            .iter()
+            .find_map(|path| ctx.read_to_string(path).ok());
+
+        collect_gpus(ctx, pci_ids.as_deref())
+    }
+}
+
+/// Lists the GPUs behind `/sys/class/drm/card*`, naming them through `pci_ids` when given.
+fn collect_gpus(ctx: &CollectionContext, pci_ids: Option<&str>) -> Result<Vec<GpuInfo>, Error> {
+    // no DRM subsystem at all on headless machines and in containers, so no GPUs
+    let drm_dir = ctx.path("/sys/class/drm");
+    let mut cards: Vec<_> = sorted_entries(&drm_dir)?
+        .into_iter()
+        // connectors show up as `card0-HDMI-A-1` and friends, we only want the cards
+        .filter(|name| {
+            name.strip_prefix("card")
+                .is_some_and(|idx| !idx.is_empty() && idx.bytes().all(|b| b.is_ascii_digit()))
+        })
+        .collect();
+    cards.sort_by_key(|name| name[4..].parse::<u32>().unwrap_or(u32::MAX));
+
+    let mut slots_seen = Vec::new();
+    let mut gpus = Vec::new();
+    for card in cards {
+        let device_dir = drm_dir.join(&card).join("device");
+
+        // devices without PCI ids (e.g. simpledrm's framebuffer) aren't real GPUs
+        let (Some(vendor_id), Some(device_id)) = (
+            read_hex_id(&device_dir.join("vendor")),
+            read_hex_id(&device_dir.join("device")),
+        ) else {
+            continue;
+        };
+
+        let uevent = read(&device_dir.join("uevent")).unwrap_or_default();
+        let slot = uevent_value(&uevent, "PCI_SLOT_NAME");
+        if let Some(slot) = &slot {
+            if slots_seen.contains(slot) {
+                continue;
+            }
+            slots_seen.push(slot.clone());
+        }
+
+        let driver = uevent_value(&uevent, "DRIVER").or_else(|| {
+            fs::read_link(device_dir.join("driver"))
+                .ok()
+                .and_then(|link| Some(link.file_name()?.to_string_lossy().into_owned()))
+        });
+
+        let (vendor_name, device_name) = pci_ids
+            .map(|ids| lookup_pci_ids(ids, vendor_id, device_id))
+            .unwrap_or_default();
+
+        let vendor = vendor_name
+            .map(|name| short_vendor_name(&name))
+            .or_else(|| known_vendor_name(vendor_id).map(str::to_string));
+        let model = device_name
+            .map(|name| short_device_name(&name))
+            .unwrap_or_else(|| format!("Device {device_id:04x}"));
+
+        gpus.push(GpuInfo {
+            vendor,
+            model,
+            driver,
+            pci_id: format!("{vendor_id:04x}:{device_id:04x}"),
+            integrated: is_integrated(&device_dir, vendor_id, slot.as_deref()),
+        });
+    }
+
+    Ok(gpus)
+}
+
+/// Whether the GPU is integrated, when its vendor or memory gives that away.
+///
+/// Where a GPU sits on the PCI bus says little, AMD's integrated GPUs sit behind a bridge
+/// like add-in cards do.
+fn is_integrated(device_dir: &Path, vendor_id: u16, slot: Option<&str>) -> Option<bool> {
+    match vendor_id {
+        // NVIDIA's PCI GPUs are all discrete
+        0x10de => Some(false),
+        // Intel's integrated graphics are always device 2, function 0 of the root bus
+        0x8086 => slot.map(|slot| slot.ends_with(":00:02.0")),
+        _ => {
+            let bytes = |file: &str| read(&device_dir.join(file))?.parse::<u64>().ok();
+            // amdgpu tells how much VRAM there is and how much the CPU can see at once. Only
+            // dedicated VRAM is ever bigger than that window, the memory an APU borrows from
+            // RAM is visible whole, as is VRAM with resizable BAR, so that says nothing.
+            match (
+                bytes("mem_info_vram_total"),
+                bytes("mem_info_vis_vram_total"),
+            ) {
+                (Some(total), Some(visible)) if visible < total => Some(false),
+                _ => None,
+            }
+        }
+    }
+}
+
+fn read_hex_id(path: &Path) -> Option<u16> {
+    u16::from_str_radix(read(path)?.trim_start_matches("0x"), 16).ok()
+}
+
+fn uevent_value(uevent: &str, key: &str) -> Option<String> {
+    uevent.lines().find_map(|line| {
+        line.strip_prefix(key)?
+            .strip_prefix('=')
+            .map(|value| value.trim().to_string())
+    })
+}
+
+/// Looks up the vendor and device names in the text of a `pci.ids` database.
+fn lookup_pci_ids(
+    pci_ids: &str,
+    vendor_id: u16,
+    device_id: u16,
+) -> (Option<String>, Option<String>) {
+    let vendor_key = format!("{vendor_id:04x}");
+    let device_key = format!("{device_id:04x}");
+
+    let mut vendor_name = None;
+    for line in pci_ids.lines() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+
+        match vendor_name {
+            None => {
+                // vendor lines aren't indented, device classes start at the first `C` line
+                if line.starts_with("C ") {
+                    break;
+                }
+                if let Some(name) = line.strip_prefix(&vendor_key) {
+                    vendor_name = Some(name.trim().to_string());
+                }
+            }
+            Some(_) => {
+                let Some(device_line) = line.strip_prefix('\t') else {
+                    // next vendor, the device isn't listed
+                    break;
+                };
+                if let Some(name) = device_line.strip_prefix(&device_key) {
+                    return (vendor_name, Some(name.trim().to_string()));
+                }
+            }
+        }
+    }
+
+    (vendor_name, None)
+}
+
+/// Fallback names for the usual suspects when no `pci.ids` is installed.
+fn known_vendor_name(vendor_id: u16) -> Option<&'static str> {
+    match vendor_id {
+        0x1002 => Some("AMD"),
+        0x10de => Some("NVIDIA"),
+        0x8086 => Some("Intel"),
+        0x1af4 => Some("Virtio"),
+        0x1234 => Some("QEMU"),
+        0x15ad => Some("VMware"),
+        0x80ee => Some("VirtualBox"),
+        0x5143 => Some("Qualcomm"),
+        _ => None,
+    }
+}
+
+/// `Advanced Micro Devices, Inc. [AMD/ATI]` -> `AMD/ATI`, `NVIDIA Corporation` -> `NVIDIA`
+fn short_vendor_name(name: &str) -> String {
+    if let Some(bracketed) = bracketed(name) {
+        return bracketed.to_string();
+    }
+    name.trim_end_matches(", Inc.")
+        .trim_end_matches(" Corporation")
+        .trim_end_matches(" Inc.")
+        .trim()
+        .to_string()
+}
+
+/// `AD104 [GeForce RTX 4070]` -> `GeForce RTX 4070`
+fn short_device_name(name: &str) -> String {
+    bracketed(name).unwrap_or(name).to_string()
+}
+
+fn bracketed(name: &str) -> Option<&str> {
+    let start = name.rfind('[')?;
+    let end = name[start..].find(']')? + start;
+    Some(&name[start + 1..end]).filter(|inner| !inner.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::context::fixtures::{fixture, offline};
+    use testresult::TestResult;
+
+    const PCI_IDS: &str = "\
+# comment
+1002  Advanced Micro Devices, Inc. [AMD/ATI]
+\t73bf  Navi 21 [Radeon RX 6800/6800 XT / 6900 XT]
+\t\t1002 0e3a  Radeon RX 6900 XT
+8086  Intel Corporation
+\t9a49  TigerLake-LP GT2 [Iris Xe Graphics]
+10de  NVIDIA Corporation
+C 03  Display controller
+\t00  VGA compatible controller
+";
+
+    #[test]
+    fn test_collect_integrated_and_discrete() -> TestResult {
+        let root = fixture(&[
+            // a discrete card without resizable BAR, which the CPU sees a window of
+            ("/sys/class/drm/card1/device/vendor", "0x1002\n"),
+            ("/sys/class/drm/card1/device/device", "0x73bf\n"),
+            (
+                "/sys/class/drm/card1/device/uevent",
+                "DRIVER=amdgpu\nPCI_SLOT_NAME=0000:03:00.0\n",
+            ),
+            (
+                "/sys/class/drm/card1/device/mem_info_vram_total",
+                "17163091968\n",
+            ),
+            (
+                "/sys/class/drm/card1/device/mem_info_vis_vram_total",
+                "268435456\n",
+            ),
+            ("/sys/class/drm/card0/device/vendor", "0x8086\n"),
+            ("/sys/class/drm/card0/device/device", "0x9a49\n"),
+            (
+                "/sys/class/drm/card0/device/uevent",
+                "DRIVER=i915\nPCI_SLOT_NAME=0000:00:02.0\n",
+            ),
+            ("/sys/class/drm/card0-eDP-1/status", "connected\n"),
+            // an APU's GPU, off the root bus and with all of its borrowed memory visible
+            ("/sys/class/drm/card2/device/vendor", "0x1002\n"),
+            ("/sys/class/drm/card2/device/device", "0x1681\n"),
+            (
+                "/sys/class/drm/card2/device/uevent",
+                "DRIVER=amdgpu\nPCI_SLOT_NAME=0000:c5:00.0\n",
+            ),
+            (
+                "/sys/class/drm/card2/device/mem_info_vram_total",
+                "536870912\n",
+            ),
+            (
+                "/sys/class/drm/card2/device/mem_info_vis_vram_total",
+                "536870912\n",
+            ),
+        ]);
+
+        let gpus = collect_gpus(&offline(&root), Some(PCI_IDS))?;
+
+        assert_eq!(
+            gpus,
+            vec![
+                GpuInfo {
+                    vendor: Some("Intel".to_string()),
+                    model: "Iris Xe Graphics".to_string(),
+                    driver: Some("i915".to_string()),
+                    pci_id: "8086:9a49".to_string(),
+                    integrated: Some(true),
+                },
+                GpuInfo {
+                    vendor: Some("AMD/ATI".to_string()),
+                    model: "Radeon RX 6800/6800 XT / 6900 XT".to_string(),
+                    driver: Some("amdgpu".to_string()),
+                    pci_id: "1002:73bf".to_string(),
+                    integrated: Some(false),
+                },
+                GpuInfo {
+                    vendor: Some("AMD/ATI".to_string()),
+                    model: "Device 1681".to_string(),
+                    driver: Some("amdgpu".to_string()),
+                    pci_id: "1002:1681".to_string(),
+                    integrated: None,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_without_pci_ids() -> TestResult {
+        let root = fixture(&[
+            ("/sys/class/drm/card0/device/vendor", "0x10de\n"),
+            ("/sys/class/drm/card0/device/device", "0x2684\n"),
+            ("/sys/bus/pci/drivers/nvidia/bind", ""),
+        ]);
+        std::os::unix::fs::symlink(
+            root.path().join("sys/bus/pci/drivers/nvidia"),
+            root.path().join("sys/class/drm/card0/device/driver"),
+        )?;
+
+        let gpus = collect_gpus(&offline(&root), None)?;
+
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].vendor.as_deref(), Some("NVIDIA"));
+        assert_eq!(gpus[0].model, "Device 2684");
+        assert_eq!(gpus[0].driver.as_deref(), Some("nvidia"));
+        assert_eq!(gpus[0].integrated, Some(false));
+        Ok(())
+    }
+
+    #[test]
+    fn test_skips_devices_without_pci_ids() -> TestResult {
+        // simpledrm's framebuffer
+        let root = fixture(&[(
+            "/sys/class/drm/card0/device/uevent",
+            "DRIVER=simple-framebuffer\n",
+        )]);
+        assert!(collect_gpus(&offline(&root), Some(PCI_IDS))?.is_empty());
+
+        // no DRM subsystem at all
+        let root = fixture(&[]);
+        assert!(collect_gpus(&offline(&root), Some(PCI_IDS))?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lookup_pci_ids() {
+        assert_eq!(
+            lookup_pci_ids(PCI_IDS, 0x10de, 0x2684),
+            (Some("NVIDIA Corporation".to_string()), None)
+        );
+        assert_eq!(lookup_pci_ids(PCI_IDS, 0x1234, 0x1111), (None, None));
+    }
+}
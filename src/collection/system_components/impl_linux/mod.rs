@@ -0,0 +1,929 @@
+//! Linux collectors, reading everything through the [`CollectionContext`]'s root so they can
+//! run against fixtures and offline systems as well as the live machine.
+
+use anyhow::{Context, Error};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tracing::debug;
+
+use super::{
+    shell_version, BoardModel, Cpu, CurrentShell, DesktopEnvironment, Hostname, OperatingSystem,
+    SystemComponent, SystemMemory, TerminalEmulator, WindowManager, OS_INFO_DATA,
+};
+use crate::collection::context::CollectionContext;
+use crate::collection::info::{
+    BoardModelInfo, CpuCache, CpuInfo, DesktopEnvironmentInfo, HostnameInfo, MemoryInfo,
+    MemorySlot, OsInfo, ShellInfo, SwapDevice, TerminalInfo, WindowManagerInfo,
+};
+
+mod battery;
+mod disk;
+mod gpu;
+mod net;
+
+/// Shells skipped over when walking up from the shell to find the terminal.
+const COMMON_SHELLS: &[&str] = &[
+    "sh", "su", "nu", "bash", "fish", "dash", "tcsh", "zsh", "ksh", "csh", "sudo", "doas",
+];
+
+/// Process names of window managers and compositors, and how to show them.
+const KNOWN_WINDOW_MANAGERS: &[(&str, &str)] = &[
+    ("sway", "Sway"),
+    ("Hyprland", "Hyprland"),
+    ("river", "river"),
+    ("niri", "niri"),
+    ("wayfire", "Wayfire"),
+    ("labwc", "labwc"),
+    ("weston", "Weston"),
+    ("kwin_wayland", "KWin"),
+    ("kwin_x11", "KWin"),
+    ("gnome-shell", "Mutter"),
+    ("mutter", "Mutter"),
+    ("muffin", "Muffin"),
+    ("marco", "Marco"),
+    ("xfwm4", "Xfwm4"),
+    ("openbox", "Openbox"),
+    ("i3", "i3"),
+    ("bspwm", "bspwm"),
+    ("awesome", "awesome"),
+    ("dwm", "dwm"),
+    ("herbstluftwm", "herbstluftwm"),
+    ("qtile", "Qtile"),
+    ("xmonad", "xmonad"),
+    ("fluxbox", "Fluxbox"),
+    ("icewm", "IceWM"),
+    ("spectrwm", "spectrwm"),
+    ("leftwm", "LeftWM"),
+    ("enlightenment", "Enlightenment"),
+];
+
+/// Values firmware vendors leave in DMI fields they didn't bother filling in.
+const DMI_PLACEHOLDERS: &[&str] = &[
+    "to be filled by o.e.m.",
+    "system product name",
+    "system manufacturer",
+    "system version",
+    "default string",
+    "not applicable",
+    "not specified",
+    "o.e.m.",
+    "none",
+];
+
+impl SystemComponent for Cpu {
+    type Info = CpuInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let cpuinfo = ctx
+            .read_to_string("/proc/cpuinfo")
+            .context("Failed to read /proc/cpuinfo.")?;
+        let processors = parse_cpuinfo(&cpuinfo);
+        let first = processors
+            .first()
+            .ok_or(Error::msg("No processors listed in /proc/cpuinfo."))?;
+
+        let model = ["model name", "Hardware", "Processor", "cpu model"]
+            .iter()
+            .find_map(|key| first.get(key))
+            .map(|model| model.to_string())
+            .ok_or(Error::msg("Failed to get CPU model name."))?;
+
+        let threads = processors.len();
+        let physical_cores: HashSet<_> = processors
+            .iter()
+            .filter_map(|cpu| Some((cpu.get("physical id")?, cpu.get("core id")?)))
+            .collect();
+        let cores = if physical_cores.is_empty() {
+            threads
+        } else {
+            physical_cores.len()
+        };
+
+        let cpufreq = |file: &str| {
+            ctx.read_to_string(format!("/sys/devices/system/cpu/cpu0/cpufreq/{file}"))
+                .ok()
+                .and_then(|khz| khz.trim().parse::<u64>().ok())
+                .map(|khz| khz / 1000)
+                .filter(|mhz| *mhz > 0)
+        };
+        let cpuinfo_mhz = first
+            .get("cpu MHz")
+            .and_then(|mhz| mhz.parse::<f64>().ok())
+            .map(|mhz| mhz.round() as u64)
+            .filter(|mhz| *mhz > 0);
+        // prefer the advertised maximum over whatever the clock happens to be right now
+        let frequency_mhz = cpufreq("cpuinfo_max_freq").or(cpuinfo_mhz);
+
+        // arm calls them features
+        let flags = ["flags", "Features"]
+            .iter()
+            .find_map(|key| first.get(key))
+            .map(|flags| flags.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Ok(vec![CpuInfo {
+            model,
+            vendor: first.get("vendor_id").map(|vendor| vendor.to_string()),
+            cores,
+            threads,
+            frequency_mhz,
+            min_frequency_mhz: cpufreq("cpuinfo_min_freq"),
+            current_frequency_mhz: cpufreq("scaling_cur_freq").or(cpuinfo_mhz),
+            microcode: first
+                .get("microcode")
+                .map(|microcode| microcode.to_string()),
+            caches: cpu_caches(ctx),
+            flags,
+        }])
+    }
+}
+
+/// Caches of the first core, from `/sys/devices/system/cpu/cpu0/cache`.
+fn cpu_caches(ctx: &CollectionContext) -> Vec<CpuCache> {
+    let mut indices: Vec<_> = ctx
+        .path("/sys/devices/system/cpu/cpu0/cache")
+        .read_dir()
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("index"))
+        })
+        .collect();
+    indices.sort();
+
+    indices
+        .into_iter()
+        .filter_map(|index| {
+            let read = |file: &str| {
+                std::fs::read_to_string(index.join(file))
+                    .ok()
+                    .map(|value| value.trim().to_string())
+            };
+            Some(CpuCache {
+                level: read("level")?.parse().ok()?,
+                kind: read("type")?,
+                size_bytes: parse_cache_size(&read("size")?)?,
+            })
+        })
+        .collect()
+}
+
+/// Parses sizes like `32K` or `8192K` from sysfs' cache descriptions.
+fn parse_cache_size(size: &str) -> Option<u64> {
+    let (amount, multiplier) = match size.char_indices().last()? {
+        (at, 'K') => (&size[..at], 1024),
+        (at, 'M') => (&size[..at], 1024 * 1024),
+        (at, 'G') => (&size[..at], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+    amount.parse::<u64>().ok().map(|amount| amount * multiplier)
+}
+
+impl SystemComponent for SystemMemory {
+    type Info = MemoryInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let meminfo = ctx
+            .read_to_string("/proc/meminfo")
+            .context("Failed to read /proc/meminfo.")?;
+        let meminfo = parse_meminfo(&meminfo);
+
+        let total_bytes = *meminfo
+            .get("MemTotal")
+            .ok_or(Error::msg("Failed to get total memory."))?;
+        let swap_total_bytes = meminfo.get("SwapTotal").copied().filter(|swap| *swap > 0);
+        let swap_devices = ctx
+            .read_to_string("/proc/swaps")
+            .map(|swaps| parse_swaps(&swaps))
+            .unwrap_or_default();
+
+        Ok(vec![MemoryInfo {
+            total_bytes,
+            swap_total_bytes,
+            slots: memory_slots(ctx),
+            swap_devices,
+        }])
+    }
+}
+
+/// Memory slots from the SMBIOS memory device (type 17) tables, usually only readable by root.
+fn memory_slots(ctx: &CollectionContext) -> Vec<MemorySlot> {
+    let mut entries: Vec<_> = ctx
+        .path("/sys/firmware/dmi/entries")
+        .read_dir()
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("17-"))
+        })
+        .collect();
+    // `17-10` has to come after `17-9`
+    entries.sort_by_key(|path| {
+        path.file_name()
+            .and_then(|name| name.to_str()?.strip_prefix("17-")?.parse::<u32>().ok())
+    });
+
+    entries
+        .into_iter()
+        .filter_map(|entry| std::fs::read(entry.join("raw")).ok())
+        .filter_map(|raw| parse_memory_device(&raw))
+        .collect()
+}
+
+/// Decodes an SMBIOS memory device structure: the formatted area, then its strings.
+fn parse_memory_device(raw: &[u8]) -> Option<MemorySlot> {
+    let length = *raw.get(1)? as usize;
+    if *raw.first()? != 17 || length < 0x17 || raw.len() < length {
+        return None;
+    }
+    let byte = |offset: usize| (offset < length).then(|| raw[offset]);
+    let word = |offset: usize| {
+        (offset + 1 < length).then(|| u16::from_le_bytes([raw[offset], raw[offset + 1]]))
+    };
+    let strings: Vec<_> = raw[length..]
+        .split(|byte| *byte == 0)
+        .take_while(|string| !string.is_empty())
+        .map(|string| String::from_utf8_lossy(string).trim().to_string())
+        .collect();
+    // strings are numbered from 1, 0 means there's none
+    let string = |offset: usize| {
+        let index = byte(offset)? as usize;
+        strings
+            .get(index.checked_sub(1)?)
+            .filter(|value| {
+                let value = value.to_lowercase();
+                !value.is_empty()
+                    && value != "unknown"
+                    && !DMI_PLACEHOLDERS.contains(&value.as_str())
+            })
+            .cloned()
+    };
+
+    let size_bytes = match word(0x0C)? {
+        0 | 0xFFFF => None,
+        // the real size in MiB is in the extended size field
+        0x7FFF => {
+            let extended = raw.get(0x1C..0x20).filter(|_| length >= 0x20)?;
+            let mib = u32::from_le_bytes(extended.try_into().ok()?) & 0x7FFF_FFFF;
+            Some(mib as u64 * 1024 * 1024)
+        }
+        size if size & 0x8000 != 0 => Some((size & 0x7FFF) as u64 * 1024),
+        size => Some(size as u64 * 1024 * 1024),
+    };
+    let kind = match byte(0x12)? {
+        0x12 => Some("DDR"),
+        0x13 => Some("DDR2"),
+        0x18 => Some("DDR3"),
+        0x1A => Some("DDR4"),
+        0x1B => Some("LPDDR"),
+        0x1C => Some("LPDDR2"),
+        0x1D => Some("LPDDR3"),
+        0x1E => Some("LPDDR4"),
+        0x22 => Some("DDR5"),
+        0x23 => Some("LPDDR5"),
+        _ => None,
+    };
+    // the configured speed is what it actually runs at, the other one what it's rated for
+    let speed_mts = word(0x20)
+        .filter(|speed| *speed != 0 && *speed != 0xFFFF)
+        .or(word(0x15))
+        .filter(|speed| *speed != 0 && *speed != 0xFFFF);
+
+    Some(MemorySlot {
+        locator: string(0x10).unwrap_or("Unknown".to_string()),
+        size_bytes,
+        kind: kind.filter(|_| size_bytes.is_some()).map(str::to_string),
+        speed_mts: speed_mts.filter(|_| size_bytes.is_some()),
+        manufacturer: string(0x17),
+        part_number: string(0x1A),
+    })
+}
+
+/// Parses the table in `/proc/swaps`, whose sizes are in KiB.
+fn parse_swaps(swaps: &str) -> Vec<SwapDevice> {
+    swaps
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(SwapDevice {
+                // spaces in the path are escaped as \040
+                path: fields.next()?.replace("\\040", " "),
+                kind: fields.next()?.to_string(),
+                size_bytes: fields.next()?.parse::<u64>().ok()? * 1024,
+                used_bytes: fields.next()?.parse::<u64>().ok()? * 1024,
+            })
+        })
+        .collect()
+}
+
+impl SystemComponent for BoardModel {
+    type Info = BoardModelInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let dmi = |field: &str| {
+            ctx.read_to_string(format!("/sys/devices/virtual/dmi/id/{field}"))
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| {
+                    !value.is_empty() && !DMI_PLACEHOLDERS.contains(&value.to_lowercase().as_str())
+                })
+        };
+
+        // arm boards usually describe themselves in the device tree instead
+        let device_tree_model = || {
+            ctx.read_to_string("/sys/firmware/devicetree/base/model")
+                .ok()
+                .map(|model| model.trim_end_matches('\0').trim().to_string())
+                .filter(|model| !model.is_empty())
+        };
+
+        let name = dmi("product_name")
+            .or_else(|| dmi("board_name"))
+            .or_else(device_tree_model)
+            .unwrap_or("Generic".to_string());
+
+        Ok(vec![BoardModelInfo {
+            name,
+            vendor: dmi("sys_vendor").or_else(|| dmi("board_vendor")),
+            version: dmi("product_version"),
+        }])
+    }
+}
+
+impl SystemComponent for OperatingSystem {
+    type Info = OsInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let os_release = ctx.os_release();
+
+        let name = os_release
+            .get("PRETTY_NAME")
+            .or_else(|| os_release.get("NAME"))
+            .cloned()
+            .unwrap_or("Unknown".to_string());
+
+        let arch = ctx
+            .read_to_string("/proc/sys/kernel/arch")
+            .ok()
+            .map(|arch| arch.trim().to_string())
+            .or_else(|| {
+                ctx.is_live()
+                    .then(|| OS_INFO_DATA.architecture().map(str::to_string))
+                    .flatten()
+            });
+
+        let kernel = |file: &str| {
+            ctx.read_to_string(format!("/proc/sys/kernel/{file}"))
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        Ok(vec![OsInfo {
+            name,
+            version: os_release.get("VERSION_ID").cloned(),
+            build: os_release.get("BUILD_ID").cloned(),
+            arch,
+            kernel: kernel("osrelease"),
+            kernel_build: kernel("version"),
+        }])
+    }
+}
+
+impl SystemComponent for CurrentShell {
+    type Info = ShellInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let (pid, name) = find_shell(ctx)?;
+        let path = ctx
+            .path(format!("/proc/{pid}/exe"))
+            .read_link()
+            .ok()
+            .map(|path| path.to_string_lossy().into_owned());
+        // running the shell only makes sense on the machine it's from
+        let version = path
+            .as_deref()
+            .filter(|_| ctx.is_live())
+            .and_then(|path| shell_version(&name, path.as_ref()));
+
+        Ok(vec![ShellInfo {
+            name,
+            pid,
+            path,
+            version,
+        }])
+    }
+}
+
+impl SystemComponent for TerminalEmulator {
+    type Info = TerminalInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let from_processes = find_shell(ctx).ok().and_then(|(shell_pid, _)| {
+            let mut pid = parent_pid(ctx, shell_pid)?;
+            let mut name = process_name(ctx, pid)?;
+            while COMMON_SHELLS.contains(&name.as_str()) {
+                pid = parent_pid(ctx, pid)?;
+                name = process_name(ctx, pid)?;
+            }
+            Some(name)
+        });
+
+        let name = from_processes
+            .or_else(|| {
+                let program = ctx.env("TERM_PROGRAM")?;
+                match ctx.env("TERM_PROGRAM_VERSION") {
+                    Some(version) if !ctx.visual_toggles.hide_terminal_version => {
+                        Some(format!("{program} {version}"))
+                    }
+                    _ => Some(program.to_string()),
+                }
+            })
+            .ok_or(Error::msg("Failed to get terminal application"))?;
+
+        Ok(vec![TerminalInfo { name }])
+    }
+}
+
+impl SystemComponent for WindowManager {
+    type Info = WindowManagerInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let running: HashSet<_> = ctx
+            .path("/proc")
+            .read_dir()
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter_map(|pid| process_name(ctx, pid))
+            .collect();
+
+        if let Some((_, name)) = KNOWN_WINDOW_MANAGERS
+            .iter()
+            .find(|(process, _)| running.contains(*process))
+        {
+            return Ok(vec![WindowManagerInfo {
+                name: name.to_string(),
+            }]);
+        }
+
+        // libmacchina can also ask the X server or wayland compositor directly
+        if ctx.is_live() {
+            use libmacchina::traits::GeneralReadout as _;
+            if let Ok(name) = libmacchina::GeneralReadout::new().window_manager() {
+                return Ok(vec![WindowManagerInfo { name }]);
+            }
+        }
+
+        Err(Error::msg("Failed to get window manager"))
+    }
+}
+
+impl SystemComponent for DesktopEnvironment {
+    type Info = DesktopEnvironmentInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let desktop = ctx
+            .env("XDG_CURRENT_DESKTOP")
+            .or_else(|| ctx.env("DESKTOP_SESSION"))
+            .filter(|desktop| !desktop.eq_ignore_ascii_case("xinitrc"))
+            .ok_or(Error::msg("Failed to get desktop environment"))?;
+
+        let mut chars = desktop.chars();
+        let name = chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default();
+
+        Ok(vec![DesktopEnvironmentInfo { name }])
+    }
+}
+
+impl SystemComponent for Hostname {
+    type Info = HostnameInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let hostname = ["/proc/sys/kernel/hostname", "/etc/hostname"]
+            .iter()
+            .find_map(|path| ctx.read_to_string(path).ok())
+            .map(|hostname| hostname.trim().to_string())
+            .filter(|hostname| !hostname.is_empty())
+            .ok_or(Error::msg("Failed to get hostname."))?;
+
+        Ok(vec![HostnameInfo { hostname }])
+    }
+}
+
+/// Finds the shell we were started from, skipping over `cargo` when run through it.
+fn find_shell(ctx: &CollectionContext) -> Result<(u32, String), Error> {
+    let current_pid = ctx
+        .pid()
+        .ok_or(Error::msg("No process to look for the shell from."))?;
+    debug!("current_pid: {current_pid}");
+
+    let mut pid = current_pid;
+    loop {
+        pid = parent_pid(ctx, pid)
+            .filter(|pid| *pid > 0)
+            .ok_or(Error::msg("Failed to get parent process."))?;
+        let name = process_name(ctx, pid).ok_or(Error::msg("Failed to get parent process."))?;
+        debug!("parent {pid}: {name}");
+
+        let shell = name.strip_prefix('-').unwrap_or(&name).to_lowercase();
+        if shell != "cargo" {
+            return Ok((pid, shell));
+        }
+    }
+}
+
+fn parent_pid(ctx: &CollectionContext, pid: u32) -> Option<u32> {
+    let stat = ctx.read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // the process name is in parentheses and may contain spaces or parentheses itself
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(1)?.parse().ok()
+}
+
+fn process_name(ctx: &CollectionContext, pid: u32) -> Option<String> {
+    ctx.read_to_string(format!("/proc/{pid}/comm"))
+        .ok()
+        .map(|comm| comm.trim().to_string())
+        .filter(|comm| !comm.is_empty())
+}
+
+/// Trimmed content of a sysfs attribute, `None` when it's missing or empty.
+fn read(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Names in `dir` in sorted order, none when it doesn't exist, like a sysfs class the
+/// kernel wasn't built with.
+fn sorted_entries(dir: &Path) -> Result<Vec<String>, Error> {
+    let entries = match std::fs::read_dir(dir) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        entries => entries.with_context(|| format!("Failed to read {}", dir.display()))?,
+    };
+    let mut names: Vec<_> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Splits `/proc/cpuinfo` into one `key: value` map per logical processor.
+fn parse_cpuinfo(cpuinfo: &str) -> Vec<HashMap<&str, &str>> {
+    cpuinfo
+        .split("\n\n")
+        .map(|block| {
+            block
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .collect::<HashMap<_, _>>()
+        })
+        .filter(|processor| processor.contains_key("processor"))
+        .collect()
+}
+
+/// Reads `/proc/meminfo` into bytes per field.
+fn parse_meminfo(meminfo: &str) -> HashMap<&str, u64> {
+    meminfo
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let mut value = value.split_whitespace();
+            let amount: u64 = value.next()?.parse().ok()?;
+            let multiplier = match value.next() {
+                Some("kB") => 1024,
+                _ => 1,
+            };
+            Some((key.trim(), amount * multiplier))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::VisualToggles;
+    use crate::collection::context::fixtures::{fixture, offline};
+    use std::fs;
+    use testresult::TestResult;
+
+    /// A shell (200) running in a terminal (100) running us (300).
+    const PROCESS_TREE: &[(&str, &str)] = &[
+        ("/proc/100/comm", "kitty\n"),
+        ("/proc/100/stat", "100 (kitty) S 1 100 100 0"),
+        ("/proc/200/comm", "zsh\n"),
+        ("/proc/200/stat", "200 (zsh) S 100 200 200 0"),
+        ("/proc/300/comm", "so-sysinfo\n"),
+        ("/proc/300/stat", "300 (so-sysinfo) R 200 300 200 0"),
+    ];
+
+    #[test]
+    fn test_cpu_from_fixture() -> TestResult {
+        let mut cpuinfo = String::new();
+        for (processor, core) in [(0, 0), (1, 1), (2, 0), (3, 1)] {
+            cpuinfo += &format!(
+                "processor\t: {processor}\nvendor_id\t: GenuineIntel\n\
+                 model name\t: Intel(R) Core(TM) i5-7200U CPU @ 2.50GHz\n\
+                 cpu MHz\t\t: 2712.345\nphysical id\t: 0\ncore id\t\t: {core}\n\
+                 microcode\t: 0xf4\nflags\t\t: fpu sse2 avx2\n\n"
+            );
+        }
+        let root = fixture(&[
+            ("/proc/cpuinfo", &cpuinfo),
+            (
+                "/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_min_freq",
+                "400000\n",
+            ),
+            ("/sys/devices/system/cpu/cpu0/cache/index0/level", "1\n"),
+            ("/sys/devices/system/cpu/cpu0/cache/index0/type", "Data\n"),
+            ("/sys/devices/system/cpu/cpu0/cache/index0/size", "32K\n"),
+            ("/sys/devices/system/cpu/cpu0/cache/index3/level", "3\n"),
+            (
+                "/sys/devices/system/cpu/cpu0/cache/index3/type",
+                "Unified\n",
+            ),
+            ("/sys/devices/system/cpu/cpu0/cache/index3/size", "3072K\n"),
+        ]);
+
+        let info = Cpu::collect_info(&offline(&root))?;
+        assert_eq!(
+            info,
+            vec![CpuInfo {
+                model: "Intel(R) Core(TM) i5-7200U CPU @ 2.50GHz".to_string(),
+                vendor: Some("GenuineIntel".to_string()),
+                cores: 2,
+                threads: 4,
+                frequency_mhz: Some(2712),
+                min_frequency_mhz: Some(400),
+                current_frequency_mhz: Some(2712),
+                microcode: Some("0xf4".to_string()),
+                caches: vec![
+                    CpuCache {
+                        level: 1,
+                        kind: "Data".to_string(),
+                        size_bytes: 32 * 1024,
+                    },
+                    CpuCache {
+                        level: 3,
+                        kind: "Unified".to_string(),
+                        size_bytes: 3 * 1024 * 1024,
+                    },
+                ],
+                flags: vec!["fpu".to_string(), "sse2".to_string(), "avx2".to_string()],
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_from_fixture() -> TestResult {
+        let root = fixture(&[
+            (
+                "/proc/meminfo",
+                "MemTotal:       16303372 kB\nMemFree:         1234567 kB\nSwapTotal:       4194300 kB\n",
+            ),
+            (
+                "/proc/swaps",
+                "Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority\n\
+                 /swap\\040file\t\t\t\tfile\t\t4194300\t\t1024\t\t-2\n",
+            ),
+        ]);
+        let entry = root.path().join("sys/firmware/dmi/entries/17-0");
+        fs::create_dir_all(&entry)?;
+        fs::write(
+            entry.join("raw"),
+            memory_device(
+                16 * 1024,
+                0x1A,
+                3200,
+                &["DIMM_A1", "Kingston", "KF3200C16D4"],
+            ),
+        )?;
+        let entry = root.path().join("sys/firmware/dmi/entries/17-1");
+        fs::create_dir_all(&entry)?;
+        fs::write(entry.join("raw"), memory_device(0, 0x02, 0, &["DIMM_B1"]))?;
+
+        let info = SystemMemory::collect_info(&offline(&root))?;
+        assert_eq!(
+            info,
+            vec![MemoryInfo {
+                total_bytes: 16303372 * 1024,
+                swap_total_bytes: Some(4194300 * 1024),
+                slots: vec![
+                    MemorySlot {
+                        locator: "DIMM_A1".to_string(),
+                        size_bytes: Some(16 * 1024 * 1024 * 1024),
+                        kind: Some("DDR4".to_string()),
+                        speed_mts: Some(3200),
+                        manufacturer: Some("Kingston".to_string()),
+                        part_number: Some("KF3200C16D4".to_string()),
+                    },
+                    MemorySlot {
+                        locator: "DIMM_B1".to_string(),
+                        size_bytes: None,
+                        kind: None,
+                        speed_mts: None,
+                        manufacturer: None,
+                        part_number: None,
+                    },
+                ],
+                swap_devices: vec![SwapDevice {
+                    path: "/swap file".to_string(),
+                    kind: "file".to_string(),
+                    size_bytes: 4194300 * 1024,
+                    used_bytes: 1024 * 1024,
+                }],
+            }]
+        );
+        Ok(())
+    }
+
+    /// An SMBIOS 2.8 memory device of `size_mib`, with locator, manufacturer and part number
+    /// taken from the first three of `strings`.
+    fn memory_device(size_mib: u16, kind: u8, speed: u16, strings: &[&str]) -> Vec<u8> {
+        let mut raw = vec![0; 0x28];
+        raw[0] = 17;
+        raw[1] = 0x28;
+        raw[0x0C..0x0E].copy_from_slice(&size_mib.to_le_bytes());
+        raw[0x10] = 1;
+        raw[0x12] = kind;
+        raw[0x15..0x17].copy_from_slice(&speed.to_le_bytes());
+        for (offset, index) in [(0x17, 2), (0x1A, 3)] {
+            if strings.len() >= index {
+                raw[offset] = index as u8;
+            }
+        }
+        for string in strings {
+            raw.extend(string.as_bytes());
+            raw.push(0);
+        }
+        raw.push(0);
+        raw
+    }
+
+    #[test]
+    fn test_board_model_skips_placeholders() -> TestResult {
+        let root = fixture(&[
+            (
+                "/sys/devices/virtual/dmi/id/product_name",
+                "System Product Name\n",
+            ),
+            ("/sys/devices/virtual/dmi/id/board_name", "PRIME B450M-A\n"),
+            (
+                "/sys/devices/virtual/dmi/id/sys_vendor",
+                "System manufacturer\n",
+            ),
+            (
+                "/sys/devices/virtual/dmi/id/board_vendor",
+                "ASUSTeK COMPUTER INC.\n",
+            ),
+        ]);
+
+        let info = BoardModel::collect_info(&offline(&root))?;
+        assert_eq!(
+            info,
+            vec![BoardModelInfo {
+                name: "PRIME B450M-A".to_string(),
+                vendor: Some("ASUSTeK COMPUTER INC.".to_string()),
+                version: None,
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_os_from_fixture() -> TestResult {
+        let root = fixture(&[
+            (
+                "/usr/lib/os-release",
+                "NAME=\"Arch Linux\"\nPRETTY_NAME=\"Arch Linux\"\nID=arch\nBUILD_ID=rolling\n",
+            ),
+            ("/proc/sys/kernel/arch", "x86_64\n"),
+            ("/proc/sys/kernel/osrelease", "6.10.3-arch1-1\n"),
+            (
+                "/proc/sys/kernel/version",
+                "#1 SMP PREEMPT_DYNAMIC Sat, 03 Aug 2024 01:23:45 +0000\n",
+            ),
+        ]);
+
+        let info = OperatingSystem::collect_info(&offline(&root))?;
+        assert_eq!(
+            info,
+            vec![OsInfo {
+                name: "Arch Linux".to_string(),
+                version: None,
+                build: Some("rolling".to_string()),
+                arch: Some("x86_64".to_string()),
+                kernel: Some("6.10.3-arch1-1".to_string()),
+                kernel_build: Some(
+                    "#1 SMP PREEMPT_DYNAMIC Sat, 03 Aug 2024 01:23:45 +0000".to_string()
+                ),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_shell_and_terminal_from_fixture() -> TestResult {
+        let root = fixture(PROCESS_TREE);
+        std::os::unix::fs::symlink("/usr/bin/zsh", root.path().join("proc/200/exe"))?;
+        let ctx = offline(&root).with_pid(300);
+
+        // offline, so the shell isn't run to ask for its version
+        let shell = CurrentShell::collect_info(&ctx)?;
+        assert_eq!(
+            shell,
+            vec![ShellInfo {
+                name: "zsh".to_string(),
+                pid: 200,
+                path: Some("/usr/bin/zsh".to_string()),
+                version: None,
+            }]
+        );
+
+        let terminal = TerminalEmulator::collect_info(&ctx)?;
+        assert_eq!(terminal[0].name, "kitty");
+        Ok(())
+    }
+
+    #[test]
+    fn test_terminal_from_env() -> TestResult {
+        let root = fixture(&[]);
+        let ctx = offline(&root).with_env([
+            ("TERM_PROGRAM", "WezTerm"),
+            ("TERM_PROGRAM_VERSION", "20240203"),
+        ]);
+        assert_eq!(
+            TerminalEmulator::collect_info(&ctx)?[0].name,
+            "WezTerm 20240203"
+        );
+
+        let ctx = CollectionContext::offline(
+            root.path(),
+            VisualToggles {
+                hide_terminal_version: true,
+                ..Default::default()
+            },
+        )
+        .with_env([
+            ("TERM_PROGRAM", "WezTerm"),
+            ("TERM_PROGRAM_VERSION", "20240203"),
+        ]);
+        assert_eq!(TerminalEmulator::collect_info(&ctx)?[0].name, "WezTerm");
+        Ok(())
+    }
+
+    #[test]
+    fn test_shell_without_processes() {
+        let root = fixture(&[]);
+        assert!(CurrentShell::collect_info(&offline(&root)).is_err());
+    }
+
+    #[test]
+    fn test_window_manager_from_fixture() -> TestResult {
+        let mut files = PROCESS_TREE.to_vec();
+        files.push(("/proc/50/comm", "sway\n"));
+        let root = fixture(&files);
+
+        let info = WindowManager::collect_info(&offline(&root))?;
+        assert_eq!(info[0].name, "Sway");
+        Ok(())
+    }
+
+    #[test]
+    fn test_desktop_environment_from_env() -> TestResult {
+        let root = fixture(&[]);
+
+        let ctx = offline(&root).with_env([("XDG_CURRENT_DESKTOP", "plasma")]);
+        assert_eq!(DesktopEnvironment::collect_info(&ctx)?[0].name, "Plasma");
+
+        let ctx = offline(&root).with_env([("DESKTOP_SESSION", "xinitrc")]);
+        assert!(DesktopEnvironment::collect_info(&ctx).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hostname_from_fixture() -> TestResult {
+        let root = fixture(&[("/etc/hostname", "solaara-box\n")]);
+
+        let info = Hostname::collect_info(&offline(&root))?;
+        assert_eq!(info[0].hostname, "solaara-box");
+        Ok(())
+    }
+}
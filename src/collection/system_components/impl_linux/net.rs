@@ -0,0 +1,228 @@
+use anyhow::Error;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+
+use super::{read, sorted_entries};
+use crate::collection::context::CollectionContext;
+use crate::collection::info::{NetInfo, NetLink};
+use crate::collection::system_components::{Net, SystemComponent};
+
+/// `ARPHRD_LOOPBACK` from `if_arp.h`, what `/sys/class/net/*/type` says for loopback links.
+const LOOPBACK_TYPE: &str = "772";
+
+/// IPv4 and IPv6 addresses of each interface, by name.
+type Addresses = HashMap<String, (Vec<String>, Vec<String>)>;
+
+impl SystemComponent for Net {
+    type Info = NetInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        // addresses aren't in sysfs, only the running system can be asked for them
+        let addresses = if ctx.is_live() {
+            live_addresses()
+        } else {
+            HashMap::new()
+        };
+
+        let links = collect_links(ctx, &addresses)?;
+        if links.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(vec![NetInfo { links }])
+    }
+}
+
+/// Addresses of the running system's interfaces, from `getifaddrs`.
+fn live_addresses() -> Addresses {
+    let networks = sysinfo::Networks::new_with_refreshed_list();
+    let mut addresses = Addresses::new();
+    for (name, data) in &networks {
+        let (ipv4, ipv6) = addresses.entry(name.clone()).or_default();
+        for network in data.ip_networks() {
+            match network.addr {
+                IpAddr::V4(_) => ipv4.push(network.to_string()),
+                IpAddr::V6(_) => ipv6.push(network.to_string()),
+            }
+        }
+    }
+    addresses
+}
+
+/// Lists the interfaces in `/sys/class/net`, leaving out the ones the toggles hide.
+fn collect_links(ctx: &CollectionContext, addresses: &Addresses) -> Result<Vec<NetLink>, Error> {
+    let class = ctx.path("/sys/class/net");
+
+    let toggles = ctx.visual_toggles;
+    let mut links = vec![];
+    for name in sorted_entries(&class)? {
+        let dir = class.join(&name);
+        let kind = link_kind(&dir);
+        // physical links have the device they're on, everything else was made by software
+        let is_virtual = kind != "loopback" && !dir.join("device").exists();
+        if (kind == "loopback" && toggles.hide_loopback)
+            || (is_virtual && toggles.hide_virtual_links)
+        {
+            continue;
+        }
+
+        let (ipv4, ipv6) = addresses.get(&name).cloned().unwrap_or_default();
+        links.push(NetLink {
+            kind: kind.to_string(),
+            is_virtual,
+            state: read(&dir.join("operstate")).unwrap_or_else(|| "unknown".to_string()),
+            mac: read(&dir.join("address")).filter(|mac| mac != "00:00:00:00:00:00"),
+            ipv4,
+            ipv6,
+            // down links and most virtual ones can't be asked, or say -1
+            speed_mbps: read(&dir.join("speed")).and_then(|speed| speed.parse().ok()),
+            name,
+        });
+    }
+
+    Ok(links)
+}
+
+/// What sort of link the interface at `dir` is.
+fn link_kind(dir: &Path) -> &'static str {
+    let devtype = read(&dir.join("uevent")).and_then(|uevent| {
+        uevent
+            .lines()
+            .find_map(|line| line.strip_prefix("DEVTYPE=").map(str::to_string))
+    });
+
+    if read(&dir.join("type")).as_deref() == Some(LOOPBACK_TYPE) {
+        "loopback"
+    } else if dir.join("wireless").exists()
+        || dir.join("phy80211").exists()
+        || devtype.as_deref() == Some("wlan")
+    {
+        "wireless"
+    } else if dir.join("bridge").exists() || devtype.as_deref() == Some("bridge") {
+        "bridge"
+    } else if devtype.as_deref() == Some("vlan") {
+        "VLAN"
+    } else if dir.join("device").exists() {
+        "wired"
+    } else {
+        "virtual"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::VisualToggles;
+    use crate::collection::context::fixtures::{fixture, offline};
+    use testresult::TestResult;
+
+    /// Loopback, wired and wireless links, a docker bridge, a VLAN and a VPN tunnel. Links
+    /// with a `device` are on hardware.
+    const LINKS: &[(&str, &str)] = &[
+        ("/sys/class/net/lo/type", "772\n"),
+        ("/sys/class/net/lo/address", "00:00:00:00:00:00\n"),
+        ("/sys/class/net/lo/operstate", "unknown\n"),
+        ("/sys/class/net/enp3s0/type", "1\n"),
+        ("/sys/class/net/enp3s0/address", "a8:a1:59:12:34:56\n"),
+        ("/sys/class/net/enp3s0/operstate", "up\n"),
+        ("/sys/class/net/enp3s0/speed", "1000\n"),
+        ("/sys/class/net/enp3s0/device/vendor", "0x10ec\n"),
+        ("/sys/class/net/wlan0/type", "1\n"),
+        ("/sys/class/net/wlan0/address", "3c:22:fb:ab:cd:ef\n"),
+        // a link that's down can't tell its speed
+        ("/sys/class/net/wlan0/operstate", "down\n"),
+        ("/sys/class/net/wlan0/wireless/link", "0\n"),
+        ("/sys/class/net/wlan0/device/vendor", "0x8086\n"),
+        ("/sys/class/net/docker0/type", "1\n"),
+        ("/sys/class/net/docker0/address", "02:42:ac:11:00:01\n"),
+        ("/sys/class/net/docker0/operstate", "up\n"),
+        ("/sys/class/net/docker0/bridge/stp_state", "0\n"),
+        ("/sys/class/net/enp3s0.10/type", "1\n"),
+        ("/sys/class/net/enp3s0.10/address", "a8:a1:59:12:34:56\n"),
+        ("/sys/class/net/enp3s0.10/operstate", "up\n"),
+        (
+            "/sys/class/net/enp3s0.10/uevent",
+            "DEVTYPE=vlan\nINTERFACE=enp3s0.10\n",
+        ),
+        ("/sys/class/net/tun0/type", "65534\n"),
+        ("/sys/class/net/tun0/operstate", "unknown\n"),
+        ("/sys/class/net/tun0/speed", "-1\n"),
+    ];
+
+    #[test]
+    fn test_collect_links() -> TestResult {
+        let root = fixture(LINKS);
+        let addresses = Addresses::from([(
+            "enp3s0".to_string(),
+            (
+                vec!["192.168.1.10/24".to_string()],
+                vec!["fe80::aaa1:59ff:fe12:3456/64".to_string()],
+            ),
+        )]);
+        let links = collect_links(&offline(&root), &addresses)?;
+
+        let kinds: Vec<_> = links
+            .iter()
+            .map(|link| (link.name.as_str(), link.kind.as_str(), link.is_virtual))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("docker0", "bridge", true),
+                ("enp3s0", "wired", false),
+                ("enp3s0.10", "VLAN", true),
+                ("lo", "loopback", false),
+                ("tun0", "virtual", true),
+                ("wlan0", "wireless", false),
+            ]
+        );
+        assert_eq!(
+            links[1],
+            NetLink {
+                name: "enp3s0".to_string(),
+                kind: "wired".to_string(),
+                is_virtual: false,
+                state: "up".to_string(),
+                mac: Some("a8:a1:59:12:34:56".to_string()),
+                ipv4: vec!["192.168.1.10/24".to_string()],
+                ipv6: vec!["fe80::aaa1:59ff:fe12:3456/64".to_string()],
+                speed_mbps: Some(1000),
+            }
+        );
+        assert_eq!(links[3].mac, None);
+        assert_eq!(links[4].speed_mbps, None);
+        assert_eq!(links[5].state, "down");
+        Ok(())
+    }
+
+    #[test]
+    fn test_hide_loopback_and_virtual_links() -> TestResult {
+        let root = fixture(LINKS);
+        let ctx = CollectionContext::offline(
+            root.path(),
+            VisualToggles {
+                hide_loopback: true,
+                hide_virtual_links: true,
+                ..Default::default()
+            },
+        );
+        let names: Vec<_> = collect_links(&ctx, &Addresses::new())?
+            .into_iter()
+            .map(|link| link.name)
+            .collect();
+        assert_eq!(names, vec!["enp3s0", "wlan0"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_one_node_for_all_links() -> TestResult {
+        let root = fixture(LINKS);
+        let info = Net::collect_info(&offline(&root))?;
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].links.len(), 6);
+
+        let root = fixture(&[]);
+        assert_eq!(Net::collect_info(&offline(&root))?, vec![]);
+        Ok(())
+    }
+}
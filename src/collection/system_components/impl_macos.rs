@@ -1,12 +1,19 @@
 use anyhow::Error;
 
-use crate::args::VisualToggles;
+use crate::collection::context::CollectionContext;
+use crate::collection::info::WindowManagerInfo;
 
-use super::{SystemComponent, WindowManager, SYSINFO_DATA};
+use super::{SystemComponent, WindowManager};
 
 impl SystemComponent for WindowManager {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
+    type Info = WindowManagerInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
         use std::ffi::OsStr;
+        // names are all we need, skip reading each process' memory, cpu and disk usage
+        let system = sysinfo::System::new_with_specifics(
+            sysinfo::RefreshKind::new().with_processes(sysinfo::ProcessRefreshKind::new()),
+        );
         for wm in [
             "chunkwm",
             "kwm",
@@ -15,15 +22,19 @@ impl SystemComponent for WindowManager {
             "Spectacle",
             "Rectangle",
         ] {
-            if SYSINFO_DATA
+            if system
                 .processes_by_exact_name(OsStr::new(wm))
                 .next()
                 .is_some()
             {
-                return Ok(vec![wm.to_string()]);
+                return Ok(vec![WindowManagerInfo {
+                    name: wm.to_string(),
+                }]);
             }
         }
 
-        Ok(vec!["Quartz Compositor".to_string()])
+        Ok(vec![WindowManagerInfo {
+            name: "Quartz Compositor".to_string(),
+        }])
     }
 }
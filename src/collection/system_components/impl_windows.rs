@@ -1,27 +1,40 @@
 use anyhow::Error;
 
-use crate::args::VisualToggles;
+use crate::collection::context::CollectionContext;
+use crate::collection::info::{DesktopEnvironmentInfo, TerminalInfo, WindowManagerInfo};
 
-use super::{SystemComponent, WindowManager};
+use super::{DesktopEnvironment, SystemComponent, TerminalEmulator, WindowManager};
 
 impl SystemComponent for WindowManager {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
+    type Info = WindowManagerInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
         // windows get_wm() stub...
         // TODO: do this better
-        Ok(vec!["dwm".to_string()])
+        Ok(vec![WindowManagerInfo {
+            name: "dwm".to_string(),
+        }])
     }
 }
 impl SystemComponent for DesktopEnvironment {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
+    type Info = DesktopEnvironmentInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
         // windows get_de() stub...
         // TODO: do this better
-        Ok(vec!["Aero".to_string()])
+        Ok(vec![DesktopEnvironmentInfo {
+            name: "Aero".to_string(),
+        }])
     }
 }
 impl SystemComponent for TerminalEmulator {
-    fn collect_info(visual_toggles: &VisualToggles) -> Result<Vec<String>, Error> {
+    type Info = TerminalInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
         // windows get_terminal() stub...
         // TODO: do this better
-        Ok(vec!["Unknown".to_string()])
+        Ok(vec![TerminalInfo {
+            name: "Unknown".to_string(),
+        }])
     }
 }
@@ -1,8 +1,20 @@
 use anyhow::Error;
-use libmacchina::{traits::GeneralReadout as _, traits::MemoryReadout as _};
+#[cfg(not(target_os = "linux"))]
+use libmacchina::{
+    traits::GeneralReadout as _, traits::KernelReadout as _, traits::MemoryReadout as _,
+};
+#[cfg(not(target_os = "linux"))]
 use tracing::debug;
 
-use crate::args::VisualToggles;
+use super::context::CollectionContext;
+#[cfg(not(target_os = "linux"))]
+use super::info::{
+    BatteryInfo, BoardModelInfo, CpuInfo, DesktopEnvironmentInfo, DiskInfo, GpuInfo, HostnameInfo,
+    MemoryInfo, NetInfo, OsInfo, ShellInfo, TerminalInfo, WindowManagerInfo,
+};
+
+#[cfg(target_os = "linux")]
+mod impl_linux;
 
 #[cfg(target_os = "macos")]
 mod impl_macos;
@@ -11,19 +23,47 @@ mod impl_macos;
 mod impl_windows;
 
 lazy_static::lazy_static! {
-    static ref SYSINFO_DATA: sysinfo::System = sysinfo::System::new_all();
     static ref OS_INFO_DATA: os_info::Info = os_info::get();
+}
+
+// each collector only refreshes what it reads, a full `System::new_all` walks every process
+#[cfg(not(target_os = "linux"))]
+lazy_static::lazy_static! {
+    static ref SYSINFO_CPUS: sysinfo::System = sysinfo::System::new_with_specifics(
+        sysinfo::RefreshKind::new().with_cpu(sysinfo::CpuRefreshKind::new().with_frequency()),
+    );
+    static ref SYSINFO_MEMORY: sysinfo::System = sysinfo::System::new_with_specifics(
+        sysinfo::RefreshKind::new().with_memory(sysinfo::MemoryRefreshKind::new().with_swap()),
+    );
     static ref LIBMACCHINA_GENERAL_READOUT: libmacchina::GeneralReadout = libmacchina::GeneralReadout::new();
     static ref LIBMACCHINA_MEMORY_READOUT: libmacchina::MemoryReadout = libmacchina::MemoryReadout::new();
 }
 
+/// Shells known to answer `--version`, others might start up or complain instead.
+const VERSIONED_SHELLS: &[&str] = &[
+    "bash",
+    "zsh",
+    "fish",
+    "nu",
+    "tcsh",
+    "elvish",
+    "xonsh",
+    "pwsh",
+    "powershell",
+];
+
 pub(crate) trait SystemComponent {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error>;
+    type Info;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error>;
 }
 
 pub(crate) struct Cpu;
 pub(crate) struct SystemMemory;
 pub(crate) struct Gpu;
+pub(crate) struct Disk;
+pub(crate) struct Battery;
+pub(crate) struct Net;
 pub(crate) struct BoardModel;
 pub(crate) struct OperatingSystem;
 pub(crate) struct CurrentShell;
@@ -32,237 +72,451 @@ pub(crate) struct WindowManager;
 pub(crate) struct DesktopEnvironment;
 pub(crate) struct Hostname;
 
+#[cfg(not(target_os = "linux"))]
 impl SystemComponent for Cpu {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
+    type Info = CpuInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
         // TODO: switch back to using libmacchina for this when the windows PRs get merged
         //       (PR url: https://github.com/Macchina-CLI/libmacchina/pull/145)
-        let cores = num_cpus::get();
+        let threads = num_cpus::get();
+        let cores = num_cpus::get_physical();
 
-        let cpu_model = LIBMACCHINA_GENERAL_READOUT
+        let model = LIBMACCHINA_GENERAL_READOUT
             .cpu_model_name()
             .map_err(|_| Error::msg("Failed to get CPU model name."))?;
 
-        Ok(vec![format!("{cores}x {cpu_model}")])
+        let first_cpu = SYSINFO_CPUS.cpus().first();
+        let vendor = first_cpu
+            .map(|cpu| cpu.vendor_id().trim().to_string())
+            .filter(|vendor| !vendor.is_empty());
+        let frequency_mhz = first_cpu
+            .map(|cpu| cpu.frequency())
+            .filter(|freq| *freq > 0);
+
+        Ok(vec![CpuInfo {
+            model,
+            vendor,
+            cores,
+            threads,
+            frequency_mhz,
+            ..Default::default()
+        }])
     }
 }
 
+#[cfg(not(target_os = "linux"))]
 impl SystemComponent for SystemMemory {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
+    type Info = MemoryInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
         let total_memory_in_kb = LIBMACCHINA_MEMORY_READOUT.total().unwrap();
-        Ok(vec![human_bytes::human_bytes(
-            (total_memory_in_kb * 1024) as f64,
-        )])
+        let swap_total_bytes = Some(SYSINFO_MEMORY.total_swap()).filter(|swap| *swap > 0);
+        Ok(vec![MemoryInfo {
+            total_bytes: total_memory_in_kb * 1024,
+            swap_total_bytes,
+            ..Default::default()
+        }])
     }
 }
 
+#[cfg(not(target_os = "linux"))]
 impl SystemComponent for Gpu {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
+    type Info = GpuInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
         Err(Error::msg("unimplemented"))
     }
 }
 
+#[cfg(not(target_os = "linux"))]
+impl SystemComponent for Disk {
+    type Info = DiskInfo;
+
+    /// Disks are read from `/sys/block`, so other systems get no disk nodes for now.
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        Ok(vec![])
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl SystemComponent for Battery {
+    type Info = BatteryInfo;
+
+    /// There's no `/sys/class/power_supply` to read here, so the battery node is left out.
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        Ok(vec![])
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl SystemComponent for Net {
+    type Info = NetInfo;
+
+    /// Interfaces come from `/sys/class/net` on Linux; elsewhere the network isn't drawn yet.
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        Ok(vec![])
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
 impl SystemComponent for BoardModel {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
-        Ok(vec![LIBMACCHINA_GENERAL_READOUT
-            .machine()
-            .unwrap_or("Generic".to_string())])
+    type Info = BoardModelInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        Ok(vec![BoardModelInfo {
+            name: LIBMACCHINA_GENERAL_READOUT
+                .machine()
+                .unwrap_or("Generic".to_string()),
+            vendor: None,
+            version: None,
+        }])
     }
 }
 
+#[cfg(not(target_os = "linux"))]
 impl SystemComponent for OperatingSystem {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
-        let os_name = LIBMACCHINA_GENERAL_READOUT
+    type Info = OsInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let name = LIBMACCHINA_GENERAL_READOUT
             .os_name()
             .or_else(|_| LIBMACCHINA_GENERAL_READOUT.distribution())
             .unwrap_or("Unknown".to_string());
 
-        let arch = OS_INFO_DATA
-            .architecture()
-            .map_or_else(|| "".to_string(), |arch| format!(" ({arch})"));
+        let version = match OS_INFO_DATA.version() {
+            os_info::Version::Unknown => None,
+            version => Some(version.to_string()),
+        };
+
+        let arch = OS_INFO_DATA.architecture().map(str::to_string);
 
-        Ok(vec![format!("{os_name}{arch}")])
+        Ok(vec![OsInfo {
+            name,
+            version,
+            arch,
+            kernel: libmacchina::KernelReadout::new().os_release().ok(),
+            ..Default::default()
+        }])
     }
 }
 
+#[cfg(not(target_os = "linux"))]
 impl SystemComponent for CurrentShell {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
+    type Info = ShellInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
         let current_pid =
             sysinfo::get_current_pid().map_err(|_| Error::msg("Failed to get current PID."))?;
         debug!("current_pid: {current_pid}");
-        let current_process = SYSINFO_DATA
-            .process(current_pid)
-            .ok_or(Error::msg("Process with current pid does not exist"))?;
-        debug!("current_process: {current_process:?}");
-
-        let mut parent_pid = current_process
-            .parent()
-            .ok_or(Error::msg("Failed to get parent process."))?;
-        debug!("parent_pid: {parent_pid}");
-        let mut parent_process = SYSINFO_DATA
-            .process(parent_pid)
-            .expect("Process with parent pid does not exist");
-        debug!("parent_process: {parent_process:?}");
-
-        let mut shell = parent_process
-            .name()
-            .to_string_lossy()
-            .trim()
-            .to_lowercase();
-        debug!("shell: {shell}");
-
-        shell = shell
-            .strip_prefix('-')
-            .unwrap_or(&shell)
-            .strip_suffix(".exe")
-            .unwrap_or(&shell)
-            .to_string();
-        debug!("shell(cleaned): {shell}");
+
+        // only ever refresh the processes we walk through instead of every process
+        let mut system = sysinfo::System::new();
+        let mut pid = current_pid;
 
         // recursively get parent shell process if needed
-        while matches!(shell.as_ref(), "cargo") && current_pid.as_u32() >= 1 {
-            parent_pid = parent_process
-                .parent()
+        loop {
+            let parent_pid = lookup_process(&mut system, pid)
+                .and_then(sysinfo::Process::parent)
                 .ok_or(Error::msg("Failed to get parent process."))?;
             debug!("parent_pid: {parent_pid}");
-            parent_process = SYSINFO_DATA
-                .process(parent_pid)
-                .expect("Process with parent pid does not exist");
+            let parent_process = lookup_process(&mut system, parent_pid)
+                .ok_or(Error::msg("Process with parent pid does not exist"))?;
             debug!("parent_process: {parent_process:?}");
 
-            shell = parent_process
+            let shell = parent_process
                 .name()
                 .to_string_lossy()
                 .trim()
                 .to_lowercase();
             debug!("shell: {shell}");
 
-            shell = shell
+            let shell = shell
                 .strip_prefix('-')
                 .unwrap_or(&shell)
                 .strip_suffix(".exe")
                 .unwrap_or(&shell)
                 .to_string();
             debug!("shell(cleaned): {shell}");
+
+            if shell != "cargo" {
+                let path = parent_process.exe().map(|path| path.to_path_buf());
+                let version = path.as_deref().and_then(|path| shell_version(&shell, path));
+                return Ok(vec![ShellInfo {
+                    name: shell,
+                    pid: parent_pid.as_u32(),
+                    path: path.map(|path| path.to_string_lossy().into_owned()),
+                    version,
+                }]);
+            }
+            pid = parent_pid;
         }
+    }
+}
+
+/// Refreshes a single process in `system` and returns it.
+#[cfg(not(target_os = "linux"))]
+fn lookup_process(system: &mut sysinfo::System, pid: sysinfo::Pid) -> Option<&sysinfo::Process> {
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        sysinfo::ProcessRefreshKind::new().with_exe(sysinfo::UpdateKind::OnlyIfNotSet),
+    );
+    system.process(pid)
+}
 
-        Ok(vec![shell.to_string()])
+/// Asks the shell at `path` for its version, if `name` is one that knows how to answer.
+fn shell_version(name: &str, path: &std::path::Path) -> Option<String> {
+    if !VERSIONED_SHELLS.contains(&name) {
+        return None;
     }
+    let mut command = std::process::Command::new(path);
+    command.arg("--version");
+    let output = output_within(command, SHELL_VERSION_TIMEOUT)?;
+    parse_version(&String::from_utf8_lossy(&output))
 }
 
-#[cfg(not(target_os = "windows"))]
-impl SystemComponent for TerminalEmulator {
-    fn collect_info(visual_toggles: &VisualToggles) -> Result<Vec<String>, Error> {
-        if visual_toggles.hide_terminal_version {
-            std::env::remove_var("TERM_PROGRAM_VERSION")
+/// How long a shell gets to print its version, a shell stuck on its rc files shouldn't hold
+/// up the rest of the graph.
+const SHELL_VERSION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Standard output of `command`, or `None` if it fails to start, fails or is still running
+/// after `timeout`, in which case it's killed.
+fn output_within(
+    mut command: std::process::Command,
+    timeout: std::time::Duration,
+) -> Option<Vec<u8>> {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break,
+            Ok(None) if std::time::Instant::now() < deadline => {
+                std::thread::sleep(std::time::Duration::from_millis(10))
+            }
+            Ok(None) => {
+                tracing::debug!("{command:?} timed out after {timeout:?}");
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            _ => return None,
         }
-        Ok(vec![LIBMACCHINA_GENERAL_READOUT.terminal().map_err(
-            |_| Error::msg("Failed to get terminal application"),
-        )?])
+    }
+    let mut output = vec![];
+    child.stdout.take()?.read_to_end(&mut output).ok()?;
+    Some(output)
+}
+
+/// First version number on the first line, like `5.2.15` out of
+/// `GNU bash, version 5.2.15(1)-release (x86_64-pc-linux-gnu)`.
+fn parse_version(output: &str) -> Option<String> {
+    output.lines().next()?.split_whitespace().find_map(|word| {
+        let version: String = word
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        let version = version.trim_end_matches('.');
+        (!version.is_empty() && version.contains('.')).then(|| version.to_string())
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+impl SystemComponent for TerminalEmulator {
+    type Info = TerminalInfo;
+
+    fn collect_info(ctx: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        let name = LIBMACCHINA_GENERAL_READOUT
+            .terminal()
+            .map_err(|_| Error::msg("Failed to get terminal application"))?;
+        let name = name.trim();
+        // libmacchina adds TERM_PROGRAM_VERSION to the name, other collectors read the
+        // environment at the same time so it's taken back off here rather than unset
+        let name = match ctx.env("TERM_PROGRAM_VERSION") {
+            Some(version) if ctx.visual_toggles.hide_terminal_version => {
+                name.strip_suffix(version).map_or(name, str::trim_end)
+            }
+            _ => name,
+        };
+        Ok(vec![TerminalInfo {
+            name: name.to_string(),
+        }])
     }
 }
 
-#[cfg(not(target_os = "windows"))]
-#[cfg(not(target_os = "macos"))]
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
 impl SystemComponent for WindowManager {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
-        Ok(vec![LIBMACCHINA_GENERAL_READOUT
-            .window_manager()
-            .map_err(|_| Error::msg("Failed to get window manager"))?])
+    type Info = WindowManagerInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        Ok(vec![WindowManagerInfo {
+            name: LIBMACCHINA_GENERAL_READOUT
+                .window_manager()
+                .map_err(|_| Error::msg("Failed to get window manager"))?,
+        }])
     }
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 impl SystemComponent for DesktopEnvironment {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
-        Ok(vec![LIBMACCHINA_GENERAL_READOUT
-            .desktop_environment()
-            .map_err(|_| {
-                Error::msg("Failed to get desktop environment")
-            })?])
+    type Info = DesktopEnvironmentInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        Ok(vec![DesktopEnvironmentInfo {
+            name: LIBMACCHINA_GENERAL_READOUT
+                .desktop_environment()
+                .map_err(|_| Error::msg("Failed to get desktop environment"))?,
+        }])
     }
 }
 
+#[cfg(not(target_os = "linux"))]
 impl SystemComponent for Hostname {
-    fn collect_info(_: &VisualToggles) -> Result<Vec<String>, Error> {
-        Ok(vec![LIBMACCHINA_GENERAL_READOUT
-            .hostname()
-            .map_err(|_| Error::msg("Failed to get hostname."))?])
+    type Info = HostnameInfo;
+
+    fn collect_info(_: &CollectionContext) -> Result<Vec<Self::Info>, Error> {
+        Ok(vec![HostnameInfo {
+            hostname: LIBMACCHINA_GENERAL_READOUT
+                .hostname()
+                .map_err(|_| Error::msg("Failed to get hostname."))?,
+        }])
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::args::VisualToggles;
     use testresult::TestResult;
     use tracing_test::traced_test;
 
     #[test]
     fn test_get_cpu() -> TestResult {
-        let info = Cpu::collect_info(&VisualToggles::default())?;
+        let info = Cpu::collect_info(&CollectionContext::live(VisualToggles::default()))?;
         assert!(!info.is_empty());
         Ok(())
     }
 
     #[test]
     fn test_get_system_memory() -> TestResult {
-        let info = SystemMemory::collect_info(&VisualToggles::default())?;
+        let info = SystemMemory::collect_info(&CollectionContext::live(VisualToggles::default()))?;
         assert!(!info.is_empty());
         Ok(())
     }
 
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_get_gpu() -> TestResult {
+        let _info = Gpu::collect_info(&CollectionContext::live(VisualToggles::default()))?;
+        Ok(())
+    }
+
     #[test]
     fn test_get_os() -> TestResult {
-        let info = OperatingSystem::collect_info(&VisualToggles::default())?;
+        let info =
+            OperatingSystem::collect_info(&CollectionContext::live(VisualToggles::default()))?;
         assert!(!info.is_empty());
         Ok(())
     }
 
     #[test]
     fn test_get_model() -> TestResult {
-        let _info = BoardModel::collect_info(&VisualToggles::default())?;
+        let _info = BoardModel::collect_info(&CollectionContext::live(VisualToggles::default()))?;
         Ok(())
     }
 
     #[traced_test]
     #[test]
     fn test_get_shell() -> TestResult {
-        let _info = CurrentShell::collect_info(&VisualToggles::default())?;
+        let _info = CurrentShell::collect_info(&CollectionContext::live(VisualToggles::default()))?;
         Ok(())
     }
 
     #[test]
     fn test_get_de() -> TestResult {
-        let _info = DesktopEnvironment::collect_info(&VisualToggles::default())?;
+        let _info =
+            DesktopEnvironment::collect_info(&CollectionContext::live(VisualToggles::default()))?;
         Ok(())
     }
 
     #[test]
     fn test_get_wm() -> TestResult {
-        let _info = WindowManager::collect_info(&VisualToggles::default())?;
+        let _info =
+            WindowManager::collect_info(&CollectionContext::live(VisualToggles::default()))?;
         Ok(())
     }
 
     #[test]
     fn test_get_terminal_with_version() -> TestResult {
-        let _info = TerminalEmulator::collect_info(&VisualToggles {
+        let _info = TerminalEmulator::collect_info(&CollectionContext::live(VisualToggles {
             hide_terminal_version: true,
-        })?;
+            ..Default::default()
+        }))?;
 
         Ok(())
     }
 
     #[test]
     fn test_get_terminal_without_version() -> TestResult {
-        let _info = TerminalEmulator::collect_info(&VisualToggles {
+        let _info = TerminalEmulator::collect_info(&CollectionContext::live(VisualToggles {
             hide_terminal_version: false,
-        })?;
+            ..Default::default()
+        }))?;
 
         Ok(())
     }
 
     #[test]
     fn test_get_hostname() -> TestResult {
-        let _info = Hostname::collect_info(&VisualToggles::default())?;
+        let _info = Hostname::collect_info(&CollectionContext::live(VisualToggles::default()))?;
         Ok(())
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_output_within() {
+        use std::process::Command;
+        use std::time::{Duration, Instant};
+
+        let mut echo = Command::new("sh");
+        echo.args(["-c", "echo 5.2.15"]);
+        assert_eq!(
+            output_within(echo, Duration::from_secs(5)).as_deref(),
+            Some(&b"5.2.15\n"[..])
+        );
+
+        let start = Instant::now();
+        let mut sleep = Command::new("sleep");
+        sleep.arg("5");
+        assert_eq!(output_within(sleep, Duration::from_millis(50)), None);
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        assert_eq!(
+            output_within(Command::new("false"), Duration::from_secs(5)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_version() {
+        for (output, version) in [
+            (
+                "GNU bash, version 5.2.15(1)-release (x86_64-pc-linux-gnu)\nCopyright",
+                Some("5.2.15"),
+            ),
+            ("zsh 5.9 (x86_64-pc-linux-gnu)\n", Some("5.9")),
+            ("fish, version 3.7.1\n", Some("3.7.1")),
+            ("0.95.0\n", Some("0.95.0")),
+            ("usage: 2 arguments\n", None),
+        ] {
+            assert_eq!(parse_version(output).as_deref(), version);
+        }
+    }
 }
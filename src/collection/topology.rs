@@ -0,0 +1,281 @@
+//! Which components are linked to which in the graph.
+//!
+//! Edges go between components or named groups of them. The default graph is described with
+//! the same edges and groups a config file can add, and everything is checked up front since
+//! `tui_nodes` can't lay out cycles.
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use super::SystemComponentKind;
+use SystemComponentKind::*;
+
+/// Groups every config can refer to, members that are disabled are left out.
+const BUILTIN_GROUPS: &[(&str, &[SystemComponentKind])] = &[
+    ("hardware", &[Cpu, SystemMemory, Gpu, Disk, Battery]),
+    (
+        "session",
+        &[TerminalEmulator, WindowManager, DesktopEnvironment],
+    ),
+];
+
+/// Hardware feeds the model, the model runs the OS, network links hang off the OS and the OS
+/// hosts the session.
+const DEFAULT_EDGES: &[(&str, &str)] = &[
+    ("hardware", "board_model"),
+    ("board_model", "operating_system"),
+    ("net", "operating_system"),
+    ("operating_system", "session"),
+    ("terminal_emulator", "current_shell"),
+];
+
+/// The `[graph]` section of the config file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct GraphConfig {
+    /// Whether to start from the default edges or only use `edges`.
+    pub default_edges: bool,
+    pub edges: Vec<EdgeConfig>,
+    pub groups: BTreeMap<String, Vec<SystemComponentKind>>,
+}
+
+/// An edge from a component or group to another, by id or group name.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct EdgeConfig {
+    pub from: String,
+    pub to: String,
+}
+
+impl GraphConfig {
+    /// Components a group, built-in group or component id stands for.
+    fn members(&self, name: &str) -> Option<Vec<SystemComponentKind>> {
+        self.groups
+            .get(name)
+            .cloned()
+            .or_else(|| {
+                BUILTIN_GROUPS
+                    .iter()
+                    .find(|(group, _)| *group == name)
+                    .map(|(_, members)| members.to_vec())
+            })
+            .or_else(|| SystemComponentKind::from_id(name).map(|kind| vec![kind]))
+    }
+}
+
+impl Default for GraphConfig {
+    fn default() -> Self {
+        Self {
+            default_edges: true,
+            edges: vec![],
+            groups: BTreeMap::new(),
+        }
+    }
+}
+
+/// Edges between the enabled kinds of component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Topology {
+    edges: Vec<(SystemComponentKind, SystemComponentKind)>,
+}
+
+impl Topology {
+    /// Resolves `graph` against the `enabled` components, rejecting unknown names, references
+    /// to disabled components and cycles.
+    pub fn new(graph: &GraphConfig, enabled: &[SystemComponentKind]) -> Result<Self> {
+        for (name, members) in &graph.groups {
+            if SystemComponentKind::from_id(name).is_some() {
+                bail!("Graph group `{name}` has the same name as a component.");
+            }
+            if let Some(disabled) = members.iter().find(|kind| !enabled.contains(kind)) {
+                bail!(
+                    "Graph group `{name}` refers to disabled component `{}`.",
+                    disabled.id()
+                );
+            }
+        }
+
+        // defaults quietly skip whatever is disabled, the user's own edges have to be valid
+        let defaults = DEFAULT_EDGES
+            .iter()
+            .filter(|_| graph.default_edges)
+            .map(|(from, to)| (*from, *to, false));
+        let custom = graph
+            .edges
+            .iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str(), true));
+
+        let mut edges = vec![];
+        for (from, to, strict) in defaults.chain(custom) {
+            let resolve = |name: &str| -> Result<Vec<SystemComponentKind>> {
+                let Some(members) = graph.members(name) else {
+                    bail!("Graph edge `{from} -> {to}` refers to unknown component or group `{name}`.");
+                };
+                let disabled =
+                    SystemComponentKind::from_id(name).filter(|kind| !enabled.contains(kind));
+                if let (true, Some(disabled)) = (strict, disabled) {
+                    bail!(
+                        "Graph edge `{from} -> {to}` refers to disabled component `{}`.",
+                        disabled.id()
+                    );
+                }
+                Ok(members
+                    .into_iter()
+                    .filter(|kind| enabled.contains(kind))
+                    .collect())
+            };
+
+            for from_kind in resolve(from)? {
+                for to_kind in resolve(to)? {
+                    if !edges.contains(&(from_kind, to_kind)) {
+                        edges.push((from_kind, to_kind));
+                    }
+                }
+            }
+        }
+
+        let topology = Self { edges };
+        if let Some(cycle) = topology.find_cycle() {
+            let path: Vec<_> = cycle.iter().map(|kind| kind.id()).collect();
+            bail!("Graph edges form a cycle: {}.", path.join(" -> "));
+        }
+        Ok(topology)
+    }
+
+    /// Whether there's an edge going from `from` to `to`.
+    pub fn connects(&self, from: SystemComponentKind, to: SystemComponentKind) -> bool {
+        self.edges.contains(&(from, to))
+    }
+
+    /// Returns the kinds along a cycle, starting and ending with the same one.
+    fn find_cycle(&self) -> Option<Vec<SystemComponentKind>> {
+        fn visit(
+            topology: &Topology,
+            kind: SystemComponentKind,
+            path: &mut Vec<SystemComponentKind>,
+            done: &mut Vec<SystemComponentKind>,
+        ) -> Option<Vec<SystemComponentKind>> {
+            if let Some(start) = path.iter().position(|visiting| *visiting == kind) {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(kind);
+                return Some(cycle);
+            }
+            if done.contains(&kind) {
+                return None;
+            }
+
+            path.push(kind);
+            for (_, to) in topology.edges.iter().filter(|(from, _)| *from == kind) {
+                if let Some(cycle) = visit(topology, *to, path, done) {
+                    return Some(cycle);
+                }
+            }
+            path.pop();
+            done.push(kind);
+            None
+        }
+
+        let mut done = vec![];
+        self.edges
+            .iter()
+            .find_map(|(from, _)| visit(self, *from, &mut vec![], &mut done))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+    use testresult::TestResult;
+
+    fn all() -> Vec<SystemComponentKind> {
+        SystemComponentKind::iter().collect()
+    }
+
+    fn edge(from: &str, to: &str) -> EdgeConfig {
+        EdgeConfig {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_default_topology() -> TestResult {
+        let topology = Topology::new(&GraphConfig::default(), &all())?;
+
+        assert!(topology.connects(Gpu, BoardModel));
+        assert!(topology.connects(Disk, BoardModel));
+        assert!(topology.connects(Battery, BoardModel));
+        assert!(topology.connects(Net, OperatingSystem));
+        assert!(topology.connects(OperatingSystem, WindowManager));
+        assert!(topology.connects(TerminalEmulator, CurrentShell));
+        assert!(!topology.connects(BoardModel, Cpu));
+        assert_eq!(topology.edges.len(), 11);
+        Ok(())
+    }
+
+    #[test]
+    fn test_defaults_skip_disabled() -> TestResult {
+        let enabled: Vec<_> = all().into_iter().filter(|kind| *kind != Gpu).collect();
+        let topology = Topology::new(&GraphConfig::default(), &enabled)?;
+
+        assert!(topology
+            .edges
+            .iter()
+            .all(|(from, to)| *from != Gpu && *to != Gpu));
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_groups() -> TestResult {
+        let graph = GraphConfig {
+            default_edges: false,
+            edges: vec![edge("shells", "hardware")],
+            groups: BTreeMap::from([("shells".to_string(), vec![CurrentShell, TerminalEmulator])]),
+        };
+        let topology = Topology::new(&graph, &all())?;
+
+        assert!(topology.connects(CurrentShell, Cpu));
+        assert!(topology.connects(TerminalEmulator, Gpu));
+        assert_eq!(topology.edges.len(), 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_cycles() {
+        let graph = GraphConfig {
+            edges: vec![edge("operating_system", "cpu")],
+            ..GraphConfig::default()
+        };
+        let error = Topology::new(&graph, &all()).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Graph edges form a cycle: cpu -> board_model -> operating_system -> cpu."
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_references() {
+        let enabled: Vec<_> = all().into_iter().filter(|kind| *kind != Gpu).collect();
+
+        let graph = GraphConfig {
+            edges: vec![edge("gpu", "operating_system")],
+            ..GraphConfig::default()
+        };
+        assert!(Topology::new(&graph, &enabled).is_err());
+
+        let graph = GraphConfig {
+            edges: vec![edge("floppy", "operating_system")],
+            ..GraphConfig::default()
+        };
+        assert!(Topology::new(&graph, &all()).is_err());
+
+        let graph = GraphConfig {
+            groups: BTreeMap::from([("cpu".to_string(), vec![Cpu])]),
+            ..GraphConfig::default()
+        };
+        assert!(Topology::new(&graph, &all()).is_err());
+    }
+}
@@ -0,0 +1,377 @@
+//! How many colors the terminal can show, and bringing every color drawn down to what it can.
+//!
+//! Themes are free to use any color, it's only once a frame is drawn that its cells are mapped
+//! to the nearest color the terminal has.
+
+use clap::ValueEnum;
+use ratatui::{buffer::Buffer, style::Color};
+use std::path::PathBuf;
+
+/// Whether to use colors at all, from `--color`.
+#[derive(Debug, Default, ValueEnum, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ColorChoice {
+    /// Colors when writing to a terminal and `NO_COLOR` isn't set
+    #[default]
+    Auto,
+    /// Colors even when writing to a file or pipe
+    Always,
+    /// No colors at all
+    Never,
+}
+
+/// Colors a terminal can show, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ColorSupport {
+    None,
+    /// The 16 named colors, in whatever shades the terminal's palette gives them.
+    Ansi16,
+    /// The 256-color palette.
+    Ansi256,
+    /// Any RGB color.
+    TrueColor,
+}
+
+/// `colors` is the 14th number in a terminfo entry.
+const TERMINFO_COLORS: usize = 13;
+
+/// The usual shades of the 16 named colors, xterm's defaults.
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Levels each channel takes in the 256-color palette's 6x6x6 cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorSupport {
+    /// What the terminal supports as far as the environment tells, given `choice` and whether
+    /// we're writing to a terminal at all.
+    pub fn detect(choice: ColorChoice, is_terminal: bool) -> Self {
+        Self::from_env(
+            choice,
+            is_terminal,
+            |name| std::env::var(name).ok(),
+            |term| terminfo_colors(&std::fs::read(find_terminfo(term)?).ok()?),
+        )
+    }
+
+    fn from_env(
+        choice: ColorChoice,
+        is_terminal: bool,
+        var: impl Fn(&str) -> Option<String>,
+        terminfo: impl Fn(&str) -> Option<i32>,
+    ) -> Self {
+        // empty variables count as unset, like the NO_COLOR convention says
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+
+        // the least we'll use whatever the terminal says, `None` if it's up to the terminal
+        let floor = match choice {
+            ColorChoice::Never => return Self::None,
+            ColorChoice::Always => Some(Self::Ansi16),
+            ColorChoice::Auto if var("NO_COLOR").is_some() => return Self::None,
+            ColorChoice::Auto => match var("FORCE_COLOR").as_deref() {
+                Some("0" | "false") => return Self::None,
+                Some("2") => Some(Self::Ansi256),
+                Some("3") => Some(Self::TrueColor),
+                Some(_) => Some(Self::Ansi16),
+                None => None,
+            },
+        };
+
+        let detected = Self::from_terminal(var, terminfo);
+        match floor {
+            Some(floor) => detected.max(floor),
+            None if is_terminal => detected,
+            None => Self::None,
+        }
+    }
+
+    fn from_terminal(
+        var: impl Fn(&str) -> Option<String>,
+        terminfo: impl Fn(&str) -> Option<i32>,
+    ) -> Self {
+        if matches!(var("COLORTERM").as_deref(), Some("truecolor" | "24bit")) {
+            return Self::TrueColor;
+        }
+        let Some(term) = var("TERM") else {
+            // windows terminals don't set TERM, and all the ones we run on take any color
+            return if cfg!(windows) {
+                Self::TrueColor
+            } else {
+                Self::None
+            };
+        };
+        if term == "dumb" {
+            return Self::None;
+        }
+        if term.ends_with("-direct") {
+            return Self::TrueColor;
+        }
+
+        match terminfo(&term) {
+            Some(colors) if colors >= 1 << 24 => Self::TrueColor,
+            Some(colors) if colors >= 256 => Self::Ansi256,
+            Some(colors) if colors >= 8 => Self::Ansi16,
+            Some(_) => Self::None,
+            // no terminfo entry to go by, the name is all there is
+            None if term.contains("256color") => Self::Ansi256,
+            None => Self::Ansi16,
+        }
+    }
+
+    /// `color` as close as the terminal can show it.
+    pub fn adapt(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::None, _) => Color::Reset,
+            (_, Color::Reset) | (Self::TrueColor, _) => color,
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_indexed((r, g, b))),
+            (Self::Ansi256, _) => color,
+            (Self::Ansi16, Color::Rgb(r, g, b)) => nearest_ansi16((r, g, b)),
+            (Self::Ansi16, Color::Indexed(index)) => nearest_ansi16(indexed_rgb(index)),
+            (Self::Ansi16, _) => color,
+        }
+    }
+
+    /// Recolors every cell of `buffer` with the nearest color the terminal can show.
+    pub fn adapt_buffer(self, buffer: &mut Buffer) {
+        if self == Self::TrueColor {
+            return;
+        }
+        for cell in &mut buffer.content {
+            cell.fg = self.adapt(cell.fg);
+            cell.bg = self.adapt(cell.bg);
+        }
+    }
+}
+
+/// Where the compiled terminfo entry for `term` is, searching the places ncurses does.
+fn find_terminfo(term: &str) -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".terminfo"));
+    let dirs_var = std::env::var("TERMINFO_DIRS").unwrap_or_default();
+    // an empty entry in TERMINFO_DIRS stands for the default directories
+    let dirs = std::env::var_os("TERMINFO")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(home)
+        .chain(
+            dirs_var
+                .split(':')
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from),
+        )
+        .chain(
+            ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"]
+                .into_iter()
+                .map(PathBuf::from),
+        );
+
+    let first = term.chars().next()?;
+    dirs.flat_map(|dir| {
+        // entries are filed under their first letter, or its hex code on macOS
+        [
+            dir.join(first.to_string()).join(term),
+            dir.join(format!("{:x}", first as u32)).join(term),
+        ]
+    })
+    .find(|path| path.is_file())
+}
+
+/// The `colors` number from a compiled terminfo entry, `None` if it doesn't have one.
+fn terminfo_colors(entry: &[u8]) -> Option<i32> {
+    let short = |at: usize| Some(i16::from_le_bytes(entry.get(at..at + 2)?.try_into().ok()?));
+
+    // ncurses 6.1 added a format with 32-bit numbers
+    let number_size = match short(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+    let names_size = usize::try_from(short(2)?).ok()?;
+    let bools = usize::try_from(short(4)?).ok()?;
+    let numbers = usize::try_from(short(6)?).ok()?;
+    if numbers <= TERMINFO_COLORS {
+        return None;
+    }
+
+    // numbers start on an even byte after the header, names and booleans
+    let numbers_start = (12 + names_size + bools).next_multiple_of(2);
+    let at = numbers_start + TERMINFO_COLORS * number_size;
+    let colors = match number_size {
+        2 => short(at)?.into(),
+        _ => i32::from_le_bytes(entry.get(at..at + 4)?.try_into().ok()?),
+    };
+    // negative numbers mean the capability is missing
+    (colors >= 0).then_some(colors)
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let channel = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+    channel(r1, r2) + channel(g1, g2) + channel(b1, b2)
+}
+
+/// The closest of the 256-color palette's cube and grayscale colors to `rgb`.
+fn nearest_indexed(rgb: (u8, u8, u8)) -> u8 {
+    let level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|idx| CUBE_LEVELS[*idx].abs_diff(channel))
+            .unwrap_or(0) as u8
+    };
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+    // the grayscale ramp goes from 8 to 238 in steps of 10
+    let average = (u16::from(rgb.0) + u16::from(rgb.1) + u16::from(rgb.2)) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    [cube, gray]
+        .into_iter()
+        .min_by_key(|index| distance(indexed_rgb(*index), rgb))
+        .unwrap_or(cube)
+}
+
+/// The color `index` stands for in the 256-color palette.
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[usize::from(index)].1,
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[usize::from(index / 36)],
+                CUBE_LEVELS[usize::from(index / 6 % 6)],
+                CUBE_LEVELS[usize::from(index % 6)],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+fn nearest_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, shade)| distance(*shade, rgb))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn detect(choice: ColorChoice, is_terminal: bool, env: &[(&str, &str)]) -> ColorSupport {
+        let env: HashMap<_, _> = env.iter().copied().collect();
+        ColorSupport::from_env(
+            choice,
+            is_terminal,
+            |name| env.get(name).map(|value| value.to_string()),
+            |term| match term {
+                "xterm-256color" => Some(256),
+                "xterm" => Some(8),
+                "vt100" => None,
+                "vt220" => Some(-1),
+                _ => None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_detect() {
+        use ColorChoice::*;
+        use ColorSupport::*;
+
+        let xterm = [("TERM", "xterm-256color")];
+        assert_eq!(detect(Auto, true, &xterm), Ansi256);
+        assert_eq!(detect(Auto, false, &xterm), None);
+        assert_eq!(detect(Always, false, &xterm), Ansi256);
+        assert_eq!(detect(Never, true, &xterm), None);
+        assert_eq!(detect(Auto, true, &[("TERM", "xterm")]), Ansi16);
+        assert_eq!(detect(Auto, true, &[("TERM", "vt220")]), None);
+        assert_eq!(detect(Auto, true, &[("TERM", "dumb")]), None);
+        assert_eq!(detect(Always, true, &[("TERM", "dumb")]), Ansi16);
+        // no terminfo entry for these
+        assert_eq!(detect(Auto, true, &[("TERM", "foot-256color")]), Ansi256);
+        assert_eq!(detect(Auto, true, &[("TERM", "kitty-direct")]), TrueColor);
+
+        let truecolor = [("TERM", "xterm-256color"), ("COLORTERM", "truecolor")];
+        assert_eq!(detect(Auto, true, &truecolor), TrueColor);
+        let no_color = [truecolor[0], truecolor[1], ("NO_COLOR", "1")];
+        assert_eq!(detect(Auto, true, &no_color), None);
+        assert_eq!(detect(Always, true, &no_color), TrueColor);
+        assert_eq!(detect(Auto, true, &[xterm[0], ("NO_COLOR", "")]), Ansi256);
+
+        assert_eq!(
+            detect(Auto, false, &[xterm[0], ("FORCE_COLOR", "1")]),
+            Ansi256
+        );
+        assert_eq!(detect(Auto, false, &[("FORCE_COLOR", "3")]), TrueColor);
+        assert_eq!(detect(Auto, true, &[xterm[0], ("FORCE_COLOR", "0")]), None);
+    }
+
+    #[test]
+    fn test_terminfo_colors() {
+        // header, names, booleans and numbers with `colors` set, padded after the booleans
+        let entry = |magic: i16, number_size: usize, colors: i32| {
+            let names = b"test|test terminal\0";
+            let bools = [1u8; 3];
+            let mut entry: Vec<u8> = [magic, names.len() as i16, bools.len() as i16, 15, 0, 0]
+                .iter()
+                .flat_map(|short| short.to_le_bytes())
+                .collect();
+            entry.extend(names);
+            entry.extend(bools);
+            if entry.len() % 2 == 1 {
+                entry.push(0);
+            }
+            for idx in 0..15 {
+                let number = if idx == TERMINFO_COLORS { colors } else { -1 };
+                entry.extend(&number.to_le_bytes()[..number_size]);
+            }
+            entry
+        };
+
+        assert_eq!(terminfo_colors(&entry(0o432, 2, 256)), Some(256));
+        assert_eq!(terminfo_colors(&entry(0o1036, 4, 1 << 24)), Some(1 << 24));
+        assert_eq!(terminfo_colors(&entry(0o432, 2, -1)), None);
+        assert_eq!(terminfo_colors(&entry(0o123, 2, 8)), None);
+        assert_eq!(terminfo_colors(&entry(0o432, 2, 8)[..30]), None);
+    }
+
+    #[test]
+    fn test_adapt() {
+        let gold = Color::Rgb(255, 241, 164);
+        assert_eq!(ColorSupport::TrueColor.adapt(gold), gold);
+        // (255, 255, 175) in the cube
+        assert_eq!(ColorSupport::Ansi256.adapt(gold), Color::Indexed(229));
+        assert_eq!(
+            ColorSupport::Ansi256.adapt(Color::Rgb(128, 128, 128)),
+            Color::Indexed(244)
+        );
+        assert_eq!(ColorSupport::Ansi16.adapt(gold), Color::Gray);
+        assert_eq!(
+            ColorSupport::Ansi16.adapt(Color::Rgb(250, 20, 10)),
+            Color::LightRed
+        );
+        assert_eq!(ColorSupport::Ansi16.adapt(Color::Indexed(21)), Color::Blue);
+        assert_eq!(
+            ColorSupport::Ansi16.adapt(Color::LightMagenta),
+            Color::LightMagenta
+        );
+        assert_eq!(ColorSupport::None.adapt(Color::Red), Color::Reset);
+        assert_eq!(ColorSupport::Ansi16.adapt(Color::Reset), Color::Reset);
+    }
+}
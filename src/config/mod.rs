@@ -0,0 +1,598 @@
+//! Settings read from the config file, with command line flags layered on top.
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use strum::IntoEnumIterator;
+
+use crate::args::{Args, VisualToggles};
+use crate::collection::topology::{GraphConfig, Topology};
+use crate::collection::SystemComponentKind;
+use crate::color_support::ColorChoice;
+use crate::keymap::{Action, Key, Keymap};
+use crate::logos::image::GraphicsChoice;
+use crate::logos::{self, Logo, AUTO_LOGO, DEFAULT_LOGO};
+use crate::theme::{Theme, ThemeColor, ThemeColors, ThemeName};
+
+/// Longest wait between refreshes in watch mode, in seconds. Far longer ones would overflow
+/// the clock when the next refresh is scheduled.
+const MAX_WATCH_INTERVAL: f64 = 24.0 * 60.0 * 60.0;
+
+/// The config file written out by `--print-default-config`, matching [`Config::default`].
+pub(crate) const DEFAULT_CONFIG: &str = include_str!("default.toml");
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    /// Name of the logo to draw, built in or from `logos_dir`, or `auto` for the operating
+    /// system's.
+    pub logo: String,
+    /// Directory of logo files to add to the built-in ones.
+    pub logos_dir: Option<PathBuf>,
+    /// Logo file to draw in place of the named one.
+    pub logo_file: Option<PathBuf>,
+    /// PNG to draw in place of the logo on terminals that can show images.
+    pub logo_image: Option<PathBuf>,
+    /// How to draw `logo_image`.
+    pub graphics: GraphicsChoice,
+    pub theme: ThemeName,
+    /// One color for everything but errors, in place of the theme's.
+    pub fg_color: Option<ThemeColor>,
+    /// Colors of single parts of the screen, in place of the theme's and `fg_color`.
+    pub colors: ThemeColors,
+    /// When to use colors at all.
+    pub color: ColorChoice,
+    /// Keep refreshing live metrics instead of showing a snapshot.
+    pub watch: bool,
+    /// Seconds between refreshes in watch mode.
+    pub watch_interval: f64,
+    /// Show a line of key hints under the graph.
+    pub footer: bool,
+    /// Order components are collected and drawn in, unlisted ones go last.
+    pub order: Vec<SystemComponentKind>,
+    pub toggles: Toggles,
+    pub graph: GraphConfig,
+    pub components: BTreeMap<SystemComponentKind, ComponentSettings>,
+    /// Keys to bind actions to instead of their default ones.
+    pub keys: BTreeMap<Action, Vec<Key>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ComponentSettings {
+    pub enabled: bool,
+    /// Replaces the component's node title.
+    pub title: Option<String>,
+    /// Leaves the version out of the terminal's name, only for `terminal_emulator`.
+    pub hide_version: Option<bool>,
+}
+
+/// The `[toggles]` section, switches that aren't about any one component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Toggles {
+    /// Show components that couldn't be collected as "unknown" instead of leaving them out.
+    pub show_unknown: bool,
+    pub hide_loopback: bool,
+    pub hide_virtual_links: bool,
+}
+
+impl Default for Toggles {
+    fn default() -> Self {
+        Self {
+            show_unknown: false,
+            // neither says much about the machine, and a docker host has dozens of veths
+            hide_loopback: true,
+            hide_virtual_links: true,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            logo: DEFAULT_LOGO.to_string(),
+            logos_dir: None,
+            logo_file: None,
+            logo_image: None,
+            graphics: GraphicsChoice::Auto,
+            theme: ThemeName::default(),
+            fg_color: None,
+            colors: ThemeColors::default(),
+            color: ColorChoice::Auto,
+            watch: false,
+            watch_interval: 2.0,
+            footer: true,
+            order: SystemComponentKind::iter().collect(),
+            toggles: Toggles::default(),
+            graph: GraphConfig::default(),
+            components: BTreeMap::new(),
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for ComponentSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            title: None,
+            hide_version: None,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config file `args` points at, or the default one if there is one, and
+    /// applies the command line's overrides.
+    pub fn load(args: &Args) -> Result<Self> {
+        let config = match (&args.config, default_path()) {
+            (Some(path), _) => Self::read(path)?,
+            (None, Some(path)) if path.exists() => Self::read(&path)?,
+            (None, _) => Self::default(),
+        };
+
+        let config = config.with_args(args);
+        // catch a broken graph before anything gets collected
+        config.topology()?;
+        config.keymap()?;
+        // without any IDs `auto` falls back to the default logo, which always works
+        config.logos(&[])?;
+        for (kind, settings) in &config.components {
+            if settings.hide_version.is_some() && *kind != SystemComponentKind::TerminalEmulator {
+                bail!(
+                    "`hide_version` only applies to {}, not {}.",
+                    SystemComponentKind::TerminalEmulator.id(),
+                    kind.id()
+                );
+            }
+        }
+        if !(config.watch_interval > 0.0 && config.watch_interval <= MAX_WATCH_INTERVAL) {
+            bail!("Watch interval must be a positive number of seconds, a day at most.");
+        }
+        Ok(config)
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    fn with_args(mut self, args: &Args) -> Self {
+        // a logo from the command line takes the place of the file's image too
+        if let Some(logo) = &args.logo {
+            self.logo = logo.clone();
+            self.logo_file = None;
+            self.logo_image = None;
+        }
+        if let Some(logo_file) = &args.logo_file {
+            self.logo_file = Some(logo_file.clone());
+            self.logo_image = None;
+        }
+        if let Some(logo_image) = &args.logo_image {
+            self.logo_image = Some(logo_image.clone());
+        }
+        if let Some(graphics) = args.graphics {
+            self.graphics = graphics;
+        }
+        // colors from the command line replace the file's altogether
+        if let Some(theme) = args.theme {
+            self.theme = theme;
+            self.fg_color = None;
+            self.colors = ThemeColors::default();
+        }
+        if let Some(fg_color) = args.fg_color {
+            self.fg_color = Some(fg_color);
+            self.colors = ThemeColors::default();
+        }
+        if let Some(color) = args.color {
+            self.color = color;
+        }
+        if let Some(watch_interval) = args.watch_interval {
+            self.watch_interval = watch_interval;
+        }
+        if let Some(hide_version) = args.toggles.hide_terminal_version() {
+            self.components
+                .entry(SystemComponentKind::TerminalEmulator)
+                .or_default()
+                .hide_version = Some(hide_version);
+        }
+        if let Some(show_unknown) = args.toggles.show_unknown() {
+            self.toggles.show_unknown = show_unknown;
+        }
+        if let Some(hide_loopback) = args.toggles.hide_loopback() {
+            self.toggles.hide_loopback = hide_loopback;
+        }
+        if let Some(hide_virtual_links) = args.toggles.hide_virtual_links() {
+            self.toggles.hide_virtual_links = hide_virtual_links;
+        }
+        self.watch |= args.watch;
+        self
+    }
+
+    /// Components to collect and draw, in order.
+    pub fn enabled_components(&self) -> Vec<SystemComponentKind> {
+        let unlisted = SystemComponentKind::iter().filter(|kind| !self.order.contains(kind));
+
+        let mut kinds = Vec::new();
+        for kind in self.order.iter().copied().chain(unlisted) {
+            let enabled = self.components.get(&kind).map_or(true, |c| c.enabled);
+            if enabled && !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+        kinds
+    }
+
+    pub fn watch_interval(&self) -> Duration {
+        Duration::from_secs_f64(self.watch_interval)
+    }
+
+    /// Edges between the enabled components.
+    pub fn topology(&self) -> Result<Topology> {
+        Topology::new(&self.graph, &self.enabled_components())
+    }
+
+    /// The default key bindings with the ones from `keys` in their place.
+    pub fn keymap(&self) -> Result<Keymap> {
+        Keymap::new(&self.keys)
+    }
+
+    /// The theme to draw in with the file's colors applied, followed by the other built-in
+    /// themes to cycle through.
+    pub fn themes(&self) -> Vec<Theme> {
+        let mut theme = self.theme.theme();
+        if let Some(color) = self.fg_color {
+            theme = Theme {
+                error: theme.error,
+                ..Theme::single(color.0)
+            };
+        }
+        let theme = theme.with_colors(&self.colors);
+
+        std::iter::once(theme)
+            .chain(
+                ThemeName::all()
+                    .filter(|name| *name != self.theme)
+                    .map(ThemeName::theme),
+            )
+            .collect()
+    }
+
+    /// The logo to draw followed by the others to cycle through: the built-in ones, then
+    /// those in the logos directory, which replace built-in ones of the same name. Logos of
+    /// operating systems are only there when they're the one drawn, `os_ids` picking one for
+    /// `auto`.
+    pub fn logos(&self, os_ids: &[String]) -> Result<Vec<Logo>> {
+        let mut logos = logos::builtin();
+        logos.extend(logos::distros());
+        match &self.logos_dir {
+            Some(dir) => logos::load_dir(dir, &mut logos)?,
+            None => {
+                if let Some(dir) = config_dir().map(|dir| dir.join("logos")) {
+                    if dir.is_dir() {
+                        logos::load_dir(&dir, &mut logos)?;
+                    }
+                }
+            }
+        }
+
+        if let Some(path) = &self.logo_file {
+            logos.retain(|logo| logo.ids.is_empty());
+            logos.insert(0, Logo::load(path)?);
+            return Ok(logos);
+        }
+        let selected = if self.logo == AUTO_LOGO {
+            logos::for_os(&logos, os_ids).map_or(DEFAULT_LOGO, |idx| &logos[idx].name)
+        } else {
+            &self.logo
+        };
+        let Some(selected) = logos.iter().position(|logo| logo.name == *selected) else {
+            let names: Vec<_> = logos.iter().map(|logo| logo.name.as_str()).collect();
+            bail!(
+                "Unknown logo `{}`, expected one of {}, {AUTO_LOGO}.",
+                self.logo,
+                names.join(", ")
+            );
+        };
+
+        let selected = logos.remove(selected);
+        logos.retain(|logo| logo.ids.is_empty());
+        logos.insert(0, selected);
+        Ok(logos)
+    }
+
+    /// Toggles for collectors, from the components' settings and `[toggles]`.
+    pub fn visual_toggles(&self) -> VisualToggles {
+        VisualToggles {
+            hide_terminal_version: self
+                .components
+                .get(&SystemComponentKind::TerminalEmulator)
+                .and_then(|settings| settings.hide_version)
+                .unwrap_or(false),
+            hide_loopback: self.toggles.hide_loopback,
+            hide_virtual_links: self.toggles.hide_virtual_links,
+        }
+    }
+
+    /// Title of `kind`'s node.
+    pub fn title(&self, kind: SystemComponentKind) -> &str {
+        self.components
+            .get(&kind)
+            .and_then(|settings| settings.title.as_deref())
+            .unwrap_or(kind.title())
+    }
+}
+
+/// `$XDG_CONFIG_HOME/so-sysinfo/config.toml`, falling back to `~/.config`.
+fn default_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("config.toml"))
+}
+
+/// `$XDG_CONFIG_HOME/so-sysinfo`, falling back to `~/.config`.
+fn config_dir() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        // the spec says relative paths are invalid and should be ignored
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_home.join("so-sysinfo"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use ratatui::style::Color;
+    use std::str::FromStr;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_default_config_matches() -> TestResult {
+        let config: Config = toml::from_str(DEFAULT_CONFIG)?;
+        assert_eq!(config, Config::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_components() -> TestResult {
+        let config: Config = toml::from_str(
+            r#"
+            order = ["operating_system", "cpu", "cpu"]
+
+            [components.gpu]
+            enabled = false
+
+            [components.cpu]
+            title = "[ Processor ]"
+            "#,
+        )?;
+
+        let kinds = config.enabled_components();
+        assert_eq!(
+            &kinds[..3],
+            [
+                SystemComponentKind::OperatingSystem,
+                SystemComponentKind::Cpu,
+                SystemComponentKind::SystemMemory,
+            ]
+        );
+        assert_eq!(kinds.len(), SystemComponentKind::iter().count() - 1);
+        assert!(!kinds.contains(&SystemComponentKind::Gpu));
+
+        assert_eq!(config.title(SystemComponentKind::Cpu), "[ Processor ]");
+        assert_eq!(config.title(SystemComponentKind::Gpu), "[ GPU ]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_keys() -> TestResult {
+        let config: Config = toml::from_str(
+            r#"
+            [keys]
+            quit = ["ctrl-q"]
+            cycle_logo = ["l"]
+            right = ["right"]
+            "#,
+        )?;
+        let keymap = config.keymap()?;
+        assert_eq!(
+            keymap.keys(Action::Quit).collect::<Vec<_>>(),
+            vec!["ctrl-q".parse::<Key>()?]
+        );
+        assert_eq!(keymap.keys(Action::Left).count(), 2);
+
+        assert!(toml::from_str::<Config>("[keys]\nquit = [\"hyper-q\"]").is_err());
+        assert!(toml::from_str::<Config>("[keys]\nexplode = [\"x\"]").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys() {
+        assert!(toml::from_str::<Config>("[components.floppy]\nenabled = true").is_err());
+        assert!(toml::from_str::<Config>("[components.cpu]\nenable = true").is_err());
+    }
+
+    #[test]
+    fn test_args_override_file() -> TestResult {
+        let file: Config = toml::from_str(
+            r#"
+            logo = "graffiti"
+            fg_color = "light-magenta"
+
+            [toggles]
+            show_unknown = true
+            "#,
+        )?;
+
+        let args = Args::try_parse_from(["so-sysinfo", "--logo", "shadow"])?;
+        let config = file.clone().with_args(&args);
+        assert_eq!(config.logo, "shadow");
+        assert_eq!(config.fg_color, Some(ThemeColor(Color::LightMagenta)));
+        assert!(config.toggles.show_unknown);
+
+        let args = Args::try_parse_from(["so-sysinfo", "--hide-unknown"])?;
+        assert!(!file.clone().with_args(&args).toggles.show_unknown);
+
+        let args = Args::try_parse_from(["so-sysinfo", "--theme", "nord", "-c", "#000000"])?;
+        let config = file.with_args(&args);
+        assert_eq!(config.theme, "nord".parse()?);
+        assert_eq!(config.themes()[0].logo, Color::Rgb(0, 0, 0));
+        assert!(Args::try_parse_from(["so-sysinfo", "--theme", "solarized"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_watch_interval_bounds() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "")?;
+        let load = |interval: &str| {
+            Config::load(&Args::try_parse_from([
+                "so-sysinfo".as_ref(),
+                "--config".as_ref(),
+                path.as_os_str(),
+                "--watch-interval".as_ref(),
+                interval.as_ref(),
+            ])?)
+        };
+
+        assert_eq!(load("0.5")?.watch_interval(), Duration::from_millis(500));
+        assert_eq!(load("86400")?.watch_interval(), Duration::from_secs(86400));
+        for interval in ["0", "-1", "NaN", "inf", "1e20"] {
+            assert!(load(interval).is_err(), "{interval} was accepted");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_terminal_version_toggle() -> TestResult {
+        let file: Config = toml::from_str(
+            r#"
+            [components.terminal_emulator]
+            hide_version = true
+            "#,
+        )?;
+        assert!(file.visual_toggles().hide_terminal_version);
+        assert!(!Config::default().visual_toggles().hide_terminal_version);
+
+        // flags win over the file either way, the last of a pair over the other
+        let hide = |flags: &[&str]| -> Result<bool> {
+            let args = Args::try_parse_from([&["so-sysinfo"], flags].concat())?;
+            Ok(file
+                .clone()
+                .with_args(&args)
+                .visual_toggles()
+                .hide_terminal_version)
+        };
+        assert!(hide(&[])?);
+        assert!(!hide(&["--show-terminal-version"])?);
+        assert!(hide(&[
+            "--show-terminal-version",
+            "--hide-terminal-version"
+        ])?);
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[components.current_shell]\nhide_version = true\n")?;
+        let args =
+            Args::try_parse_from(["so-sysinfo".as_ref(), "--config".as_ref(), path.as_os_str()])?;
+        assert_eq!(
+            Config::load(&args).unwrap_err().to_string(),
+            "`hide_version` only applies to terminal_emulator, not current_shell."
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_logos() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("mine.txt"), "so\nsysinfo")?;
+        let config = Config {
+            logo: "mine".to_string(),
+            logos_dir: Some(dir.path().to_path_buf()),
+            ..Config::default()
+        };
+
+        let logos = config.logos(&[])?;
+        let names: Vec<_> = logos.iter().map(|logo| logo.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["mine", "shadow", "graffiti", "sunset", "neon", "duotone"]
+        );
+        assert_eq!(logos[0].width(), 7);
+
+        let config = Config {
+            logo_file: Some(dir.path().join("mine.txt")),
+            ..Config::default()
+        };
+        assert_eq!(config.logos(&[])?[0].name, "mine");
+        assert_eq!(config.logos(&[])?[1].name, "shadow");
+
+        // distro logos only when they're picked, by name or by os-release
+        let names =
+            |logos: Vec<Logo>| -> Vec<String> { logos.into_iter().map(|logo| logo.name).collect() };
+        let config = Config {
+            logo: "auto".to_string(),
+            ..Config::default()
+        };
+        let os_ids = ["endeavouros".to_string(), "arch".to_string()];
+        assert_eq!(names(config.logos(&os_ids)?)[..2], ["arch", "shadow"]);
+        assert_eq!(
+            names(config.logos(&[])?),
+            names(Config::default().logos(&[])?)
+        );
+        let config = Config {
+            logo: "nixos".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(names(config.logos(&[])?)[0], "nixos");
+        assert_eq!(names(config.logos(&[])?).len(), 6);
+
+        let config = Config {
+            logo: "missing".to_string(),
+            logos_dir: Some(dir.path().to_path_buf()),
+            ..Config::default()
+        };
+        let error = config.logos(&[]).unwrap_err();
+        assert!(error.to_string().starts_with("Unknown logo `missing`"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_themes() -> TestResult {
+        let config: Config = toml::from_str(
+            r##"
+            theme = "gruvbox"
+            fg_color = "rgb(1, 2, 3)"
+
+            [colors]
+            edge = "#404040"
+            "##,
+        )?;
+        let themes = config.themes();
+        assert_eq!(themes.len(), ThemeName::all().count());
+        let gruvbox = ThemeName::from_str("gruvbox")?.theme();
+        assert_eq!(
+            themes[0],
+            Theme {
+                edge: Color::Rgb(64, 64, 64),
+                error: gruvbox.error,
+                ..Theme::single(Color::Rgb(1, 2, 3))
+            }
+        );
+        // the other themes follow as they are
+        assert!(!themes[1..].contains(&gruvbox));
+        assert_eq!(themes[1], ThemeName::default().theme());
+
+        assert!(toml::from_str::<Config>("theme = \"solarized\"").is_err());
+        assert!(toml::from_str::<Config>("[colors]\nlogo = \"gold\"").is_err());
+        assert!(toml::from_str::<Config>("[colors]\nframe = \"red\"").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+//! Recent samples of the watch mode's metrics, for the sparklines next to the graph.
+
+use std::collections::VecDeque;
+
+use crate::collection::metrics::Metrics;
+
+/// Keeps the last `capacity` values pushed into it, dropping the oldest ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RingBuffer<T> {
+    values: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T: Copy> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            values: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn latest(&self) -> Option<T> {
+        self.values.back().copied()
+    }
+
+    /// Up to `n` of the newest values, oldest first.
+    pub fn last(&self, n: usize) -> impl Iterator<Item = T> + '_ {
+        self.values
+            .iter()
+            .skip(self.len().saturating_sub(n))
+            .copied()
+    }
+}
+
+/// Usage history, in whole percent, of everything the side panel shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MetricsHistory {
+    pub cores: Vec<RingBuffer<u64>>,
+    pub memory: RingBuffer<u64>,
+    pub swap: RingBuffer<u64>,
+    capacity: usize,
+}
+
+impl MetricsHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            cores: vec![],
+            memory: RingBuffer::new(capacity),
+            swap: RingBuffer::new(capacity),
+            capacity,
+        }
+    }
+
+    /// Labelled rows of the side panel that fit in `height` lines: each core's usage, then
+    /// memory and swap. Memory and swap always get their rows, the cores that don't fit after
+    /// them are left out.
+    pub fn rows(&self, height: usize) -> Vec<(String, &RingBuffer<u64>)> {
+        let totals: Vec<_> = [("mem ", &self.memory)]
+            .into_iter()
+            .chain(self.swap.latest().is_some().then_some(("swp ", &self.swap)))
+            .map(|(label, values)| (label.to_string(), values))
+            .take(height)
+            .collect();
+
+        self.cores
+            .iter()
+            .enumerate()
+            .map(|(core, usage)| (format!("{core:>3} "), usage))
+            .take(height - totals.len())
+            .chain(totals)
+            .collect()
+    }
+
+    /// Adds a sample, skipping whatever `metrics` doesn't have a reading for.
+    pub fn record(&mut self, metrics: &Metrics) {
+        let percent = |(used, total): (u64, u64)| (used * 100).checked_div(total).unwrap_or(0);
+
+        if !metrics.core_usage.is_empty() {
+            // cores don't usually come and go, but start over for them if they do
+            if self.cores.len() != metrics.core_usage.len() {
+                self.cores = vec![RingBuffer::new(self.capacity); metrics.core_usage.len()];
+            }
+            for (history, usage) in self.cores.iter_mut().zip(&metrics.core_usage) {
+                history.push(usage.round().clamp(0.0, 100.0) as u64);
+            }
+        }
+        if let Some(memory) = metrics.memory {
+            self.memory.push(percent(memory));
+        }
+        if let Some(swap) = metrics.swap {
+            self.swap.push(percent(swap));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let mut buffer = RingBuffer::new(3);
+        for value in 1..=5 {
+            buffer.push(value);
+        }
+
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.latest(), Some(5));
+        assert_eq!(buffer.last(10).collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert_eq!(buffer.last(2).collect::<Vec<_>>(), vec![4, 5]);
+    }
+
+    #[test]
+    fn test_empty_ring_buffer() {
+        let mut buffer = RingBuffer::new(0);
+        buffer.push(1);
+
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(buffer.latest(), None);
+    }
+
+    #[test]
+    fn test_rows_keep_memory_and_swap() {
+        let mut history = MetricsHistory::new(2);
+        history.record(&Metrics {
+            memory: Some((1, 4)),
+            swap: Some((0, 4)),
+            core_usage: vec![10.0; 16],
+            ..Metrics::default()
+        });
+        let labels = |height| -> Vec<_> {
+            history
+                .rows(height)
+                .into_iter()
+                .map(|(label, _)| label)
+                .collect()
+        };
+
+        assert_eq!(labels(4), vec!["  0 ", "  1 ", "mem ", "swp "]);
+        assert_eq!(labels(1), vec!["mem "]);
+        assert_eq!(labels(0), Vec::<String>::new());
+        assert_eq!(labels(20).len(), 18);
+    }
+
+    #[test]
+    fn test_record_metrics() {
+        let mut history = MetricsHistory::new(2);
+        let mut metrics = Metrics {
+            memory: Some((1, 4)),
+            core_usage: vec![12.4, 99.6],
+            ..Metrics::default()
+        };
+        history.record(&metrics);
+        history.record(&Metrics::default());
+        metrics.core_usage = vec![50.0, 50.0, 50.0];
+        history.record(&metrics);
+
+        assert_eq!(history.memory.last(2).collect::<Vec<_>>(), vec![25, 25]);
+        assert_eq!(history.swap.len(), 0);
+        assert_eq!(history.cores.len(), 3);
+        assert_eq!(history.cores[0].last(2).collect::<Vec<_>>(), vec![50]);
+    }
+}
@@ -0,0 +1,371 @@
+//! What each key does in the TUI, defined in one table and overridable from the config file.
+//!
+//! Actions don't know which screen they're pressed on: `Up` moves focus in the graph and
+//! scrolls in the detail popup, it's up to the app to tell which one applies.
+
+use anyhow::{bail, Result};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+
+#[derive(strum::EnumIter, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum Action {
+    Quit,
+    ToggleHelp,
+    ToggleFooter,
+    ToggleDiagnostics,
+    CycleColor,
+    CycleLogo,
+    Left,
+    Right,
+    Up,
+    Down,
+    Next,
+    Previous,
+    Select,
+    Back,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+}
+
+impl Action {
+    /// How the action is written in the config file.
+    pub fn id(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::ToggleHelp => "toggle_help",
+            Action::ToggleFooter => "toggle_footer",
+            Action::ToggleDiagnostics => "toggle_diagnostics",
+            Action::CycleColor => "cycle_color",
+            Action::CycleLogo => "cycle_logo",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Next => "next",
+            Action::Previous => "previous",
+            Action::Select => "select",
+            Action::Back => "back",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+        }
+    }
+
+    /// What the action does, for the help overlay.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::ToggleHelp => "Show or hide this help",
+            Action::ToggleFooter => "Show or hide the hint line at the bottom",
+            Action::ToggleDiagnostics => "Show or hide collector diagnostics",
+            Action::CycleColor => "Switch to the next color",
+            Action::CycleLogo => "Switch to the next logo",
+            Action::Left => "Focus the node before this one",
+            Action::Right => "Focus the node after this one",
+            Action::Up => "Focus the node above, or scroll details up",
+            Action::Down => "Focus the node below, or scroll details down",
+            Action::Next => "Focus the next node",
+            Action::Previous => "Focus the previous node",
+            Action::Select => "Open or close the focused node's details",
+            Action::Back => "Close whatever is open, or clear focus",
+            Action::PageUp => "Scroll details up a page",
+            Action::PageDown => "Scroll details down a page",
+            Action::Top => "Scroll to the top of the details",
+            Action::Bottom => "Scroll to the bottom of the details",
+        }
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q", "ctrl-c"],
+            Action::ToggleHelp => &["?"],
+            Action::ToggleFooter => &["f"],
+            Action::ToggleDiagnostics => &["d"],
+            Action::CycleColor => &["c"],
+            Action::CycleLogo => &["L"],
+            Action::Left => &["left", "h"],
+            Action::Right => &["right", "l"],
+            Action::Up => &["up", "k"],
+            Action::Down => &["down", "j"],
+            Action::Next => &["tab"],
+            Action::Previous => &["shift-tab"],
+            Action::Select => &["enter"],
+            Action::Back => &["esc"],
+            Action::PageUp => &["pageup"],
+            Action::PageDown => &["pagedown"],
+            Action::Top => &["home", "g"],
+            Action::Bottom => &["end", "G"],
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Action {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        Action::iter()
+            .find(|action| action.id() == id)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown action `{id}`")))
+    }
+}
+
+/// A key along with the modifiers held down with it, like `ctrl-c` or `L`.
+///
+/// Shift isn't a modifier of its own: it's already in which character was typed, and
+/// shift-tab is a key of its own too.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+/// Names of the keys that aren't characters, as they're written in the config file.
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("shift-tab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("space", KeyCode::Char(' ')),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("insert", KeyCode::Insert),
+    ("delete", KeyCode::Delete),
+];
+
+impl Key {
+    fn from_event(event: &KeyEvent) -> Self {
+        Self {
+            code: event.code,
+            modifiers: event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = name;
+        // `-` on its own is a key, `ctrl--` is ctrl and `-`
+        while let Some((modifier, key)) = rest.split_once('-').filter(|(_, key)| !key.is_empty()) {
+            match modifier {
+                "ctrl" => modifiers |= KeyModifiers::CONTROL,
+                "alt" => modifiers |= KeyModifiers::ALT,
+                _ => break,
+            }
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => KEY_NAMES
+                .iter()
+                .find(|(key_name, _)| *key_name == rest)
+                .map(|(_, code)| *code)
+                .or_else(|| {
+                    let number = rest.strip_prefix('f')?.parse().ok()?;
+                    (1..=12).contains(&number).then_some(KeyCode::F(number))
+                })
+                .ok_or_else(|| anyhow::anyhow!("Unknown key `{name}`."))?,
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = anyhow::Error;
+
+    fn try_from(name: String) -> Result<Self> {
+        name.parse()
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        match KEY_NAMES.iter().find(|(_, code)| *code == self.code) {
+            Some((name, _)) => write!(f, "{name}"),
+            None => match self.code {
+                KeyCode::Char(c) => write!(f, "{c}"),
+                KeyCode::F(number) => write!(f, "f{number}"),
+                code => write!(f, "{code:?}"),
+            },
+        }
+    }
+}
+
+/// Every key binding, the defaults with the config file's changes applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Keymap {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl Keymap {
+    /// Binds each action in `overrides` to the keys given there instead of its default
+    /// ones, rejecting keys that end up bound to two actions and a quit without keys.
+    pub fn new(overrides: &BTreeMap<Action, Vec<Key>>) -> Result<Self> {
+        if overrides.get(&Action::Quit).is_some_and(Vec::is_empty) {
+            bail!("Quit needs at least one key, or there'd be no way out.");
+        }
+        let mut bindings: Vec<(Key, Action)> = vec![];
+        for action in Action::iter() {
+            let keys = match overrides.get(&action) {
+                Some(keys) => keys.clone(),
+                None => action
+                    .default_keys()
+                    .iter()
+                    .map(|name| name.parse())
+                    .collect::<Result<_>>()?,
+            };
+            for key in keys {
+                if let Some((_, bound)) = bindings.iter().find(|(bound, _)| *bound == key) {
+                    bail!(
+                        "Key `{key}` is bound to both {} and {}.",
+                        bound.id(),
+                        action.id()
+                    );
+                }
+                bindings.push((key, action));
+            }
+        }
+        Ok(Self { bindings })
+    }
+
+    /// The action bound to the key pressed in `event`.
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        let key = Key::from_event(event);
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, action)| *action)
+    }
+
+    /// Keys bound to `action`, in the order they were given.
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = Key> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(_, bound)| *bound == action)
+            .map(|(key, _)| *key)
+    }
+
+    /// Short reminder of the most useful keys, for the footer.
+    pub fn hint(&self) -> String {
+        [
+            (Action::ToggleHelp, "help"),
+            (Action::Next, "focus"),
+            (Action::Select, "details"),
+            (Action::Quit, "quit"),
+        ]
+        .into_iter()
+        .filter_map(|(action, name)| Some(format!("{} {name}", self.keys(action).next()?)))
+        .collect::<Vec<_>>()
+        .join(" · ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyEventKind;
+    use testresult::TestResult;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new_with_kind(code, modifiers, KeyEventKind::Press)
+    }
+
+    #[test]
+    fn test_parse_keys() -> TestResult {
+        for name in [
+            "q",
+            "L",
+            "?",
+            "-",
+            "ctrl-c",
+            "alt-ctrl-x",
+            "shift-tab",
+            "pagedown",
+            "f5",
+        ] {
+            let key: Key = name.parse()?;
+            assert_eq!(key.to_string().parse::<Key>()?, key);
+        }
+        assert_eq!("ctrl-c".parse::<Key>()?.to_string(), "ctrl-c");
+        assert!("hyper-q".parse::<Key>().is_err());
+        assert!("f13".parse::<Key>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_bindings() -> TestResult {
+        let keymap = Keymap::new(&BTreeMap::new())?;
+
+        // terminals send shift along with capitals and shift-tab
+        let event = press(KeyCode::Char('L'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.action(&event), Some(Action::CycleLogo));
+        let event = press(KeyCode::Char('h'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(&event), Some(Action::Left));
+        let event = press(KeyCode::Char('l'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(&event), Some(Action::Right));
+        let event = press(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(keymap.action(&event), Some(Action::Previous));
+        let event = press(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.action(&event), Some(Action::Quit));
+        let event = press(KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(&event), None);
+
+        assert_eq!(keymap.hint(), "? help · tab focus · enter details · q quit");
+        Ok(())
+    }
+
+    #[test]
+    fn test_overrides() -> TestResult {
+        let overrides = BTreeMap::from([(Action::CycleLogo, vec!["l".parse()?])]);
+        // `l` moves focus right by default
+        let error = Keymap::new(&overrides).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Key `l` is bound to both cycle_logo and right."
+        );
+
+        let overrides = BTreeMap::from([
+            (Action::CycleLogo, vec!["l".parse()?]),
+            (Action::Right, vec!["right".parse()?]),
+        ]);
+        let keymap = Keymap::new(&overrides)?;
+        let event = press(KeyCode::Char('l'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(&event), Some(Action::CycleLogo));
+        let event = press(KeyCode::Char('L'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.action(&event), None);
+
+        // other actions can be left without keys, quitting can't
+        let keymap = Keymap::new(&BTreeMap::from([(Action::CycleLogo, vec![])]))?;
+        assert_eq!(keymap.keys(Action::CycleLogo).count(), 0);
+        let error = Keymap::new(&BTreeMap::from([(Action::Quit, vec![])])).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Quit needs at least one key, or there'd be no way out."
+        );
+        Ok(())
+    }
+}
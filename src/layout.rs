@@ -0,0 +1,437 @@
+//! Decides how the system graph is laid out for a given amount of space.
+//!
+//! `tui_nodes` panics when its nodes don't fit the area it's given, so the size each
+//! layout needs is measured once up front and the draw code only ever asks it to lay
+//! out graphs that are known to fit.
+//!
+//! It also knows how focus moves between nodes, which follows the graph's connections
+//! rather than where the nodes end up on screen, and keeps track of where they did end up
+//! so mouse clicks can be told apart.
+
+use ratatui::layout::{Position, Rect, Size};
+use tui_nodes::{Connection, NodeGraph, NodeLayout};
+
+use crate::collection::CollectedNode;
+
+/// Narrowest the list layout gets before giving up, enough for a title and a bit of its body.
+const MIN_LIST_WIDTH: u16 = 16;
+
+/// Columns `tui_nodes` leaves between a node and the ones linked to it, its private `MARGIN`.
+const NODE_GAP: u16 = 5;
+
+/// How the graph should be drawn in a given area, from most to least detailed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Fit<'a> {
+    Graph(&'a [CollectedNode]),
+    List,
+    TooSmall,
+}
+
+/// Where focus can move from a node. `tui_nodes` draws connections right to left, from the
+/// node they start at to the one they end at, and stacks nodes that share one vertically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    /// Along a connection, towards the node it ends at.
+    Right,
+    /// Back along a connection, towards the node it starts at.
+    Left,
+    /// To the node stacked above, among those connected to the same node.
+    Up,
+    /// To the node stacked below.
+    Down,
+}
+
+/// Where the logo and each node were drawn in the last frame, to tell what a click landed on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Zones {
+    pub logo: Rect,
+    /// Where the logo image goes, when it's drawn in place of the ASCII logo.
+    pub image: Option<Rect>,
+    /// Each node's area by index, borders included, empty for nodes that weren't drawn.
+    pub nodes: Vec<Rect>,
+}
+
+impl Zones {
+    /// The node drawn at `position`, if there's one there.
+    pub fn node_at(&self, position: Position) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|zone| !zone.is_empty() && zone.contains(position))
+    }
+
+    /// The area `tui_nodes` draws a node's borders in around `zone`, the inside it split off.
+    pub fn with_borders(zone: Rect) -> Rect {
+        if zone.is_empty() {
+            return zone;
+        }
+        Rect::new(
+            zone.x.saturating_sub(1),
+            zone.y.saturating_sub(1),
+            zone.width + 2,
+            zone.height + 2,
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SystemGraph {
+    pub nodes: Vec<CollectedNode>,
+    pub compact_nodes: Vec<CollectedNode>,
+    pub links: Vec<Connection>,
+    full_size: Size,
+    compact_size: Size,
+}
+
+impl SystemGraph {
+    pub fn new(nodes: Vec<CollectedNode>, links: Vec<Connection>) -> Self {
+        let compact_nodes: Vec<_> = nodes.iter().map(CollectedNode::compact).collect();
+        let full_size = measure(&nodes, &links);
+        let compact_size = measure(&compact_nodes, &links);
+
+        Self {
+            nodes,
+            compact_nodes,
+            links,
+            full_size,
+            compact_size,
+        }
+    }
+
+    /// Puts the bodies of `nodes` in place of the ones that changed, measuring the layouts
+    /// again only when one of them changed size. Returns the indices of the changed nodes.
+    pub fn update(&mut self, nodes: Vec<CollectedNode>) -> Vec<usize> {
+        let mut changed = Vec::new();
+        let mut resized = false;
+        for (index, node) in nodes.into_iter().enumerate() {
+            let (Some(old), Some(old_compact)) =
+                (self.nodes.get_mut(index), self.compact_nodes.get_mut(index))
+            else {
+                break;
+            };
+            if node.body == old.body {
+                continue;
+            }
+
+            let compact = node.compact();
+            let size = |node: &CollectedNode| (node.width, node.height);
+            resized |= size(&node) != size(old) || size(&compact) != size(old_compact);
+            *old = node;
+            *old_compact = compact;
+            changed.push(index);
+        }
+
+        if resized {
+            self.full_size = measure(&self.nodes, &self.links);
+            self.compact_size = measure(&self.compact_nodes, &self.links);
+        }
+        changed
+    }
+
+    /// Picks the most detailed layout that fits in `area`.
+    pub fn fit(&self, area: Size) -> Fit<'_> {
+        let fits = |size: Size| size.width <= area.width && size.height <= area.height;
+
+        if fits(self.full_size) {
+            Fit::Graph(&self.nodes)
+        } else if fits(self.compact_size) {
+            Fit::Graph(&self.compact_nodes)
+        } else if fits(self.list_size()) {
+            Fit::List
+        } else {
+            Fit::TooSmall
+        }
+    }
+
+    /// Height needed to show the graph when `width` columns are available.
+    pub fn height_for_width(&self, width: u16) -> u16 {
+        [self.full_size, self.compact_size, self.list_size()]
+            .into_iter()
+            .find(|size| size.width <= width)
+            .map_or(1, |size| size.height)
+    }
+
+    /// The node focus moves to from `node` in `direction`, if there's one that way.
+    pub fn neighbor(&self, node: usize, direction: Direction) -> Option<usize> {
+        let mut outgoing: Vec<_> = self
+            .links
+            .iter()
+            .filter(|link| link.from_node == node)
+            .collect();
+        outgoing.sort_by_key(|link| link.from_port);
+        let mut incoming: Vec<_> = self
+            .links
+            .iter()
+            .filter(|link| link.to_node == node)
+            .collect();
+        incoming.sort_by_key(|link| link.to_port);
+
+        match direction {
+            Direction::Right => outgoing.first().map(|link| link.to_node),
+            Direction::Left => incoming.first().map(|link| link.from_node),
+            Direction::Up | Direction::Down => {
+                // the nodes stacked with this one, in the order `tui_nodes` stacks them
+                let siblings: Vec<usize> = match outgoing.first() {
+                    Some(parent) => {
+                        let mut links: Vec<_> = self
+                            .links
+                            .iter()
+                            .filter(|link| link.to_node == parent.to_node)
+                            .collect();
+                        links.sort_by_key(|link| link.to_port);
+                        links.into_iter().map(|link| link.from_node).collect()
+                    }
+                    None => (0..self.nodes.len())
+                        .filter(|node| self.links.iter().all(|link| link.from_node != *node))
+                        .collect(),
+                };
+                let position = siblings.iter().position(|sibling| *sibling == node)?;
+                match direction {
+                    Direction::Up => position.checked_sub(1),
+                    _ => Some(position + 1),
+                }
+                .and_then(|position| siblings.get(position).copied())
+            }
+        }
+    }
+
+    fn list_size(&self) -> Size {
+        Size::new(MIN_LIST_WIDTH, self.nodes.len() as u16)
+    }
+}
+
+/// Lays the graph out on a canvas big enough for any arrangement and returns the space it took.
+///
+/// Graphs that somehow don't fit the canvas measure as too big for any area, so they're
+/// drawn as a list rather than with nodes missing.
+fn measure(nodes: &[CollectedNode], links: &[Connection]) -> Size {
+    // nodes are placed a gap to the left of the one they link to and below the ones placed
+    // before them, so all of them side by side and all of them stacked is as big as it gets
+    let canvas = Rect::new(
+        0,
+        0,
+        nodes.iter().map(|node| node.width + NODE_GAP).sum(),
+        nodes.iter().map(|node| node.height).sum(),
+    );
+
+    let mut graph = NodeGraph::new(
+        nodes
+            .iter()
+            .map(|node| NodeLayout::new((node.width, node.height)))
+            .collect(),
+        links.to_vec(),
+        canvas.width.into(),
+        canvas.height.into(),
+    );
+    graph.calculate();
+
+    // zones are the nodes' insides and nodes grow from the right edge of the canvas
+    let zones = graph.split(canvas);
+    if zones.iter().any(|zone| zone.is_empty()) {
+        return Size::new(u16::MAX, u16::MAX);
+    }
+    zones.into_iter().fold(Size::new(0, 0), |size, zone| {
+        Size::new(
+            size.width.max(canvas.right() + 1 - zone.left()),
+            size.height.max(zone.bottom() + 1),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::info::{BoardModelInfo, ComponentInfo, DiskInfo, Drive};
+
+    fn node(name: &str) -> CollectedNode {
+        CollectedNode::new(
+            "[ Model ]",
+            ComponentInfo::BoardModel(BoardModelInfo {
+                name: name.to_string(),
+                vendor: None,
+                version: None,
+            }),
+            (1, 1),
+        )
+    }
+
+    #[test]
+    fn test_measure_chain() {
+        let nodes = vec![node("left"), node("right")];
+        let links = vec![Connection::new(0, 0, 1, 0)];
+
+        let size = measure(&nodes, &links);
+        // two 11 wide nodes with a 5 cell gap between them
+        assert_eq!(size, Size::new(27, 3));
+    }
+
+    #[test]
+    fn test_measured_size_fits() {
+        let graphs = [
+            (2, vec![Connection::new(0, 0, 1, 0)]),
+            // three nodes leading into one, which leads on to another
+            (
+                5,
+                vec![
+                    Connection::new(0, 0, 3, 0),
+                    Connection::new(1, 0, 3, 1),
+                    Connection::new(2, 0, 3, 2),
+                    Connection::new(3, 0, 4, 0),
+                ],
+            ),
+            (3, vec![]),
+        ];
+
+        for (count, links) in graphs {
+            let nodes: Vec<_> = (0..count).map(|_| node("x")).collect();
+            let size = measure(&nodes, &links);
+
+            // laid out in exactly the measured area, every node is kept and the outermost
+            // ones touch its left and bottom edges
+            let mut graph = NodeGraph::new(
+                nodes
+                    .iter()
+                    .map(|node| NodeLayout::new((node.width, node.height)))
+                    .collect(),
+                links.clone(),
+                size.width.into(),
+                size.height.into(),
+            );
+            graph.calculate();
+            let zones: Vec<_> = graph
+                .split(Rect::new(0, 0, size.width, size.height))
+                .into_iter()
+                .map(Zones::with_borders)
+                .collect();
+            assert!(zones.iter().all(|zone| !zone.is_empty()));
+            assert_eq!(zones.iter().map(|zone| zone.left()).min(), Some(0));
+            assert_eq!(
+                zones.iter().map(|zone| zone.bottom()).max(),
+                Some(size.height)
+            );
+        }
+    }
+
+    #[test]
+    fn test_multiline_node() {
+        let disk = |device: &str| Drive {
+            device: device.to_string(),
+            model: None,
+            size_bytes: 1024,
+            kind: None,
+            removable: false,
+            filesystems: vec![],
+        };
+        let node = CollectedNode::new(
+            "[ Disk ]",
+            ComponentInfo::Disk(DiskInfo {
+                disks: vec![disk("/dev/sda"), disk("/dev/nvme0n1")],
+            }),
+            (1, 0),
+        );
+
+        assert_eq!(node.body, " /dev/sda (1 KiB)     \n /dev/nvme0n1 (1 KiB) ");
+        // the longest line and the borders, each line and the borders
+        assert_eq!((node.width, node.height), (24, 4));
+    }
+
+    #[test]
+    fn test_fit_degrades() {
+        let graph = SystemGraph::new(
+            vec![node(&"a very long board model name ".repeat(3)), node("b")],
+            vec![Connection::new(0, 0, 1, 0)],
+        );
+
+        assert!(matches!(graph.fit(Size::new(200, 10)), Fit::Graph(nodes) if nodes == graph.nodes));
+        assert!(
+            matches!(graph.fit(Size::new(60, 10)), Fit::Graph(nodes) if nodes == graph.compact_nodes)
+        );
+        assert_eq!(graph.fit(Size::new(20, 2)), Fit::List);
+        assert_eq!(graph.fit(Size::new(20, 1)), Fit::TooSmall);
+    }
+
+    #[test]
+    fn test_update_in_place() {
+        let links = vec![Connection::new(0, 0, 1, 0)];
+        let mut graph = SystemGraph::new(vec![node("left"), node("right")], links.clone());
+        let full_size = graph.full_size;
+
+        // same sizes, so only the changed body is swapped in
+        assert_eq!(graph.update(vec![node("left"), node("after")]), vec![1]);
+        assert_eq!(graph.nodes[1].body.trim(), "after");
+        assert_eq!(graph.compact_nodes[1].body.trim(), "after");
+        assert_eq!(graph.full_size, full_size);
+        assert_eq!(
+            graph.update(vec![node("left"), node("after")]),
+            Vec::<usize>::new()
+        );
+
+        // a node that grew gets the layout measured again
+        let nodes = vec![node("a much longer left"), node("after")];
+        assert_eq!(graph.update(nodes.clone()), vec![0]);
+        assert_eq!(graph.full_size, SystemGraph::new(nodes, links).full_size);
+        assert!(graph.full_size.width > full_size.width);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        // 0 and 1 both lead to 2, which leads to 3, and 4 stands on its own
+        let graph = SystemGraph::new(
+            (0..5).map(|_| node("x")).collect(),
+            vec![
+                Connection::new(0, 0, 2, 0),
+                Connection::new(1, 0, 2, 1),
+                Connection::new(2, 0, 3, 0),
+            ],
+        );
+
+        assert_eq!(graph.neighbor(0, Direction::Right), Some(2));
+        assert_eq!(graph.neighbor(2, Direction::Right), Some(3));
+        assert_eq!(graph.neighbor(3, Direction::Right), None);
+        assert_eq!(graph.neighbor(3, Direction::Left), Some(2));
+        assert_eq!(graph.neighbor(2, Direction::Left), Some(0));
+        assert_eq!(graph.neighbor(0, Direction::Left), None);
+        assert_eq!(graph.neighbor(0, Direction::Down), Some(1));
+        assert_eq!(graph.neighbor(1, Direction::Up), Some(0));
+        assert_eq!(graph.neighbor(1, Direction::Down), None);
+        // nodes without outgoing connections are stacked together
+        assert_eq!(graph.neighbor(3, Direction::Down), Some(4));
+        assert_eq!(graph.neighbor(4, Direction::Up), Some(3));
+    }
+
+    #[test]
+    fn test_zones() {
+        let nodes = vec![node("left"), node("right")];
+        let links = vec![Connection::new(0, 0, 1, 0)];
+        let area = Rect::new(0, 0, 27, 3);
+        let mut graph = NodeGraph::new(
+            nodes
+                .iter()
+                .map(|node| NodeLayout::new((node.width, node.height)))
+                .collect(),
+            links,
+            area.width.into(),
+            area.height.into(),
+        );
+        graph.calculate();
+        let zones = Zones {
+            logo: Rect::default(),
+            image: None,
+            nodes: graph
+                .split(area)
+                .into_iter()
+                .map(Zones::with_borders)
+                .collect(),
+        };
+
+        assert_eq!(
+            zones.nodes,
+            vec![Rect::new(0, 0, 11, 3), Rect::new(16, 0, 11, 3)]
+        );
+        // borders count, the gap between the nodes doesn't
+        assert_eq!(zones.node_at(Position::new(0, 0)), Some(0));
+        assert_eq!(zones.node_at(Position::new(10, 2)), Some(0));
+        assert_eq!(zones.node_at(Position::new(13, 1)), None);
+        assert_eq!(zones.node_at(Position::new(16, 1)), Some(1));
+        assert_eq!(zones.node_at(Position::new(27, 1)), None);
+    }
+}
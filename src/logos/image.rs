@@ -0,0 +1,505 @@
+//! Logos drawn as images, on terminals that can show them.
+//!
+//! ratatui only knows about text, so the header gets a blank area as big as the image and the
+//! image is written over it once the frame is on screen. Terminals we don't know to show
+//! images get the ASCII logo instead.
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::ValueEnum;
+use ratatui::crossterm::{
+    cursor::{MoveTo, RestorePosition, SavePosition},
+    queue,
+};
+use ratatui::layout::Position;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::Path;
+
+/// The most rows an image takes up, as many as the tallest built-in logo.
+const MAX_ROWS: u16 = 16;
+
+/// Pixels in a cell when the terminal doesn't tell, about what most fonts come to.
+pub(crate) const DEFAULT_CELL: (u16, u16) = (8, 16);
+
+/// Base64 bytes in each piece of a Kitty image, the most the protocol allows.
+const KITTY_CHUNK: usize = 4096;
+
+/// Takes every Kitty image off the screen and frees their data.
+const KITTY_DELETE: &[u8] = b"\x1b_Ga=d,d=A,q=2\x1b\\";
+
+/// Which image protocol to use, from `--graphics`.
+#[derive(Debug, Default, ValueEnum, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum GraphicsChoice {
+    /// Whatever the terminal is known to support, if anything
+    #[default]
+    Auto,
+    /// Kitty's graphics protocol, also in Ghostty and Konsole
+    Kitty,
+    /// Sixel, in foot, mlterm, xterm with `-ti vt340` and others
+    Sixel,
+    /// iTerm2's inline images, also in WezTerm and mintty
+    Iterm2,
+    /// Always draw the ASCII logo
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GraphicsProtocol {
+    Kitty,
+    Sixel,
+    Iterm2,
+}
+
+/// Terminals known to show images, by an environment variable they set and its value.
+const TERMINALS: &[(&str, &str, GraphicsProtocol)] = &[
+    ("TERM", "xterm-kitty", GraphicsProtocol::Kitty),
+    ("TERM", "xterm-ghostty", GraphicsProtocol::Kitty),
+    ("TERM_PROGRAM", "ghostty", GraphicsProtocol::Kitty),
+    ("TERM_PROGRAM", "iTerm.app", GraphicsProtocol::Iterm2),
+    // set over ssh too
+    ("LC_TERMINAL", "iTerm2", GraphicsProtocol::Iterm2),
+    ("TERM_PROGRAM", "WezTerm", GraphicsProtocol::Iterm2),
+    ("TERM_PROGRAM", "mintty", GraphicsProtocol::Iterm2),
+    ("TERM", "foot", GraphicsProtocol::Sixel),
+    ("TERM", "foot-extra", GraphicsProtocol::Sixel),
+    ("TERM", "mlterm", GraphicsProtocol::Sixel),
+    ("TERM", "contour", GraphicsProtocol::Sixel),
+];
+
+impl GraphicsProtocol {
+    /// The protocol `choice` asks for, or the one the terminal is known to support.
+    pub fn detect(choice: GraphicsChoice) -> Option<Self> {
+        Self::from_env(choice, |name| std::env::var(name).ok())
+    }
+
+    fn from_env(choice: GraphicsChoice, var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+        match choice {
+            GraphicsChoice::Kitty => return Some(Self::Kitty),
+            GraphicsChoice::Sixel => return Some(Self::Sixel),
+            GraphicsChoice::Iterm2 => return Some(Self::Iterm2),
+            GraphicsChoice::Never => return None,
+            GraphicsChoice::Auto => {}
+        }
+
+        // multiplexers need the sequences wrapped to pass them on, and whatever's outside
+        // them might not show images anyway
+        let term = var("TERM").unwrap_or_default();
+        if var("TMUX").is_some() || term.starts_with("screen") || term.starts_with("tmux") {
+            return None;
+        }
+        if var("KITTY_WINDOW_ID").is_some() {
+            return Some(Self::Kitty);
+        }
+        TERMINALS
+            .iter()
+            .find(|(name, value, _)| var(name).as_deref() == Some(*value))
+            .map(|(_, _, protocol)| *protocol)
+    }
+}
+
+/// A logo image encoded for the terminal, drawn in place of the configured ASCII logo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ImageLogo {
+    protocol: GraphicsProtocol,
+    /// Columns and rows the image covers.
+    size: (u16, u16),
+    /// What draws the image at the cursor.
+    sequence: Vec<u8>,
+}
+
+impl ImageLogo {
+    /// Reads the PNG at `path` and encodes it for `protocol`, with `cell` pixels per cell.
+    pub fn load(path: &Path, protocol: GraphicsProtocol, cell: (u16, u16)) -> Result<Self> {
+        let png = std::fs::read(path)
+            .with_context(|| format!("Failed to read logo image {}", path.display()))?;
+        Self::new(&png, protocol, cell)
+            .with_context(|| format!("Failed to decode logo image {}", path.display()))
+    }
+
+    pub fn new(png: &[u8], protocol: GraphicsProtocol, cell: (u16, u16)) -> Result<Self> {
+        let image = decode(png)?;
+        let (cols, rows) = cells(image.width, image.height, cell);
+        let sequence = match protocol {
+            GraphicsProtocol::Kitty => kitty(png, cols, rows),
+            GraphicsProtocol::Iterm2 => iterm2(png, cols, rows),
+            // sixels are pixels, so they're scaled to fit the cells here
+            GraphicsProtocol::Sixel => {
+                let (width, height) = (cols as u32 * cell.0 as u32, rows as u32 * cell.1 as u32);
+                let scale = f64::min(
+                    width as f64 / image.width as f64,
+                    height as f64 / image.height as f64,
+                );
+                let width = ((image.width as f64 * scale) as u32).max(1);
+                let height = ((image.height as f64 * scale) as u32).max(1);
+                sixel(&image.resize(width, height))
+            }
+        };
+
+        Ok(Self {
+            protocol,
+            size: (cols, rows),
+            sequence,
+        })
+    }
+
+    /// Columns and rows the image covers.
+    pub fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    /// Whether the image stays on screen when the cells under it are drawn over, so it only
+    /// needs drawing again when it moves.
+    pub fn persists(&self) -> bool {
+        self.protocol == GraphicsProtocol::Kitty
+    }
+
+    /// Writes the image with its top left corner at `position`, leaving the cursor be.
+    pub fn draw(&self, writer: &mut impl Write, position: Position) -> std::io::Result<()> {
+        queue!(writer, SavePosition, MoveTo(position.x, position.y))?;
+        writer.write_all(&self.sequence)?;
+        queue!(writer, RestorePosition)
+    }
+
+    /// Takes the image off the screen, for images that drawing over doesn't get rid of.
+    pub fn clear(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        if self.persists() {
+            writer.write_all(KITTY_DELETE)?;
+        }
+        Ok(())
+    }
+}
+
+/// Columns and rows an image of `width` by `height` pixels covers with `cell` pixels per
+/// cell, scaled down to no more than [`MAX_ROWS`].
+fn cells(width: u32, height: u32, (cell_width, cell_height): (u16, u16)) -> (u16, u16) {
+    let (cell_width, cell_height) = (cell_width.max(1) as u32, cell_height.max(1) as u32);
+    let rows = height.div_ceil(cell_height).clamp(1, MAX_ROWS as u32);
+    // the width follows from the rows, to keep the image's proportions
+    let cols = (width * rows * cell_height).div_ceil(height * cell_width);
+    (cols.clamp(1, u16::MAX as u32) as u16, rows as u16)
+}
+
+/// Pixels of a decoded image, four bytes of RGBA each, row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pixels {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+impl Pixels {
+    fn get(&self, x: u32, y: u32) -> [u8; 4] {
+        let idx = (y * self.width + x) as usize * 4;
+        [
+            self.rgba[idx],
+            self.rgba[idx + 1],
+            self.rgba[idx + 2],
+            self.rgba[idx + 3],
+        ]
+    }
+
+    /// The image scaled to `width` by `height`, taking the nearest pixel.
+    fn resize(&self, width: u32, height: u32) -> Self {
+        let rgba = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                self.get(
+                    (x * self.width / width).min(self.width - 1),
+                    (y * self.height / height).min(self.height - 1),
+                )
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            rgba,
+        }
+    }
+}
+
+fn decode(png: &[u8]) -> Result<Pixels> {
+    let mut decoder = png::Decoder::new(png);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    let pixels = &buffer[..info.buffer_size()];
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels
+            .chunks(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        png::ColorType::Grayscale => pixels
+            .iter()
+            .flat_map(|gray| [*gray, *gray, *gray, 255])
+            .collect(),
+        png::ColorType::Indexed => bail!("Indexed colors should have been expanded."),
+    };
+    if info.width == 0 || info.height == 0 {
+        bail!("Image is empty.");
+    }
+    Ok(Pixels {
+        width: info.width,
+        height: info.height,
+        rgba,
+    })
+}
+
+/// Kitty's graphics protocol, sending the PNG as it is in pieces and placing it over `cols`
+/// by `rows` cells without moving the cursor.
+fn kitty(png: &[u8], cols: u16, rows: u16) -> Vec<u8> {
+    let data = STANDARD.encode(png);
+    let chunks: Vec<_> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+    let mut out = vec![];
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(idx + 1 < chunks.len());
+        if idx == 0 {
+            write!(out, "\x1b_Ga=T,f=100,c={cols},r={rows},C=1,q=2,m={more};")
+        } else {
+            write!(out, "\x1b_Gm={more};")
+        }
+        .unwrap();
+        out.extend_from_slice(chunk);
+        out.extend_from_slice(b"\x1b\\");
+    }
+    out
+}
+
+/// iTerm2's inline image, the PNG as it is stretched over `cols` by `rows` cells.
+fn iterm2(png: &[u8], cols: u16, rows: u16) -> Vec<u8> {
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={cols};height={rows};preserveAspectRatio=1:{}\x07",
+        png.len(),
+        STANDARD.encode(png)
+    )
+    .into_bytes()
+}
+
+/// Sixel, in the colors of the 256-color palette's 6x6x6 cube, leaving transparent pixels as
+/// they are.
+fn sixel(image: &Pixels) -> Vec<u8> {
+    // `None` for pixels that are mostly transparent
+    let color = |x, y| {
+        let [r, g, b, a] = image.get(x, y);
+        let level = |channel: u8| (channel as u16 * 5 + 127) / 255;
+        (a >= 128).then(|| level(r) * 36 + level(g) * 6 + level(b))
+    };
+
+    let mut out = vec![];
+    // no aspect ratio of its own, transparent background, then the size in pixels
+    write!(out, "\x1bP0;1;0q\"1;1;{};{}", image.width, image.height).unwrap();
+    let used: BTreeSet<u16> = (0..image.height)
+        .flat_map(|y| (0..image.width).map(move |x| (x, y)))
+        .filter_map(|(x, y)| color(x, y))
+        .collect();
+    for idx in &used {
+        let percent = |level: u16| level * 100 / 5;
+        let (r, g, b) = (idx / 36, idx / 6 % 6, idx % 6);
+        write!(out, "#{idx};2;{};{};{}", percent(r), percent(g), percent(b)).unwrap();
+    }
+
+    for top in (0..image.height).step_by(6) {
+        let band = top..(top + 6).min(image.height);
+        let colors: BTreeSet<u16> = band
+            .clone()
+            .flat_map(|y| (0..image.width).map(move |x| (x, y)))
+            .filter_map(|(x, y)| color(x, y))
+            .collect();
+        for (nth, idx) in colors.iter().enumerate() {
+            // back to the start of the band for each color after the first
+            if nth > 0 {
+                out.push(b'$');
+            }
+            write!(out, "#{idx}").unwrap();
+            let sixels = (0..image.width).map(|x| {
+                let bits = band
+                    .clone()
+                    .filter(|y| color(x, *y) == Some(*idx))
+                    .fold(0u8, |bits, y| bits | 1 << (y - top));
+                b'?' + bits
+            });
+            write_runs(&mut out, sixels);
+        }
+        out.push(b'-');
+    }
+    out.extend_from_slice(b"\x1b\\");
+    out
+}
+
+/// Writes `sixels` with runs of four or more of the same one shortened to `!<count><sixel>`,
+/// and nothing for the empty ones at the end.
+fn write_runs(out: &mut Vec<u8>, sixels: impl Iterator<Item = u8>) {
+    let mut runs: Vec<(u8, usize)> = vec![];
+    for sixel in sixels {
+        match runs.last_mut() {
+            Some((last, count)) if *last == sixel => *count += 1,
+            _ => runs.push((sixel, 1)),
+        }
+    }
+    if runs.last().is_some_and(|(sixel, _)| *sixel == b'?') {
+        runs.pop();
+    }
+    for (sixel, count) in runs {
+        if count > 3 {
+            write!(out, "!{count}").unwrap();
+            out.push(sixel);
+        } else {
+            out.extend(std::iter::repeat(sixel).take(count));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testresult::TestResult;
+
+    fn png(width: u32, height: u32, color: png::ColorType, data: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        let mut encoder = png::Encoder::new(&mut out, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(data).unwrap();
+        writer.finish().unwrap();
+        out
+    }
+
+    #[test]
+    fn test_detect() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        let detect = |choice, vars| GraphicsProtocol::from_env(choice, env(vars));
+        let auto = GraphicsChoice::Auto;
+
+        assert_eq!(
+            detect(auto, &[("TERM", "xterm-kitty")]),
+            Some(GraphicsProtocol::Kitty)
+        );
+        assert_eq!(
+            detect(auto, &[("KITTY_WINDOW_ID", "1")]),
+            Some(GraphicsProtocol::Kitty)
+        );
+        assert_eq!(
+            detect(auto, &[("TERM", "foot")]),
+            Some(GraphicsProtocol::Sixel)
+        );
+        assert_eq!(
+            detect(auto, &[("TERM_PROGRAM", "WezTerm")]),
+            Some(GraphicsProtocol::Iterm2)
+        );
+        assert_eq!(detect(auto, &[("TERM", "xterm-256color")]), None);
+        assert_eq!(
+            detect(auto, &[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux")]),
+            None
+        );
+        assert_eq!(detect(GraphicsChoice::Never, &[("TERM", "foot")]), None);
+        assert_eq!(
+            detect(GraphicsChoice::Sixel, &[]),
+            Some(GraphicsProtocol::Sixel)
+        );
+    }
+
+    #[test]
+    fn test_cells() {
+        assert_eq!(cells(100, 50, (10, 20)), (12, 3));
+        // scaled down to the most rows, keeping the proportions
+        assert_eq!(cells(1000, 1000, (10, 20)), (32, 16));
+        assert_eq!(cells(1, 1, (0, 0)), (1, 1));
+    }
+
+    #[test]
+    fn test_decode() -> TestResult {
+        let rgb = decode(&png(2, 1, png::ColorType::Rgb, &[255, 0, 0, 0, 0, 255]))?;
+        assert_eq!((rgb.width, rgb.height), (2, 1));
+        assert_eq!(rgb.rgba, [255, 0, 0, 255, 0, 0, 255, 255]);
+
+        let gray = decode(&png(1, 1, png::ColorType::GrayscaleAlpha, &[7, 9]))?;
+        assert_eq!(gray.rgba, [7, 7, 7, 9]);
+        assert!(decode(b"not a png").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_kitty() {
+        assert_eq!(
+            kitty(b"PNG", 4, 2),
+            b"\x1b_Ga=T,f=100,c=4,r=2,C=1,q=2,m=0;UE5H\x1b\\"
+        );
+
+        // 3 bytes make 4 in base64, so this takes two pieces
+        let out = String::from_utf8(kitty(&[0; KITTY_CHUNK / 4 * 3 + 3], 1, 1)).unwrap();
+        let pieces: Vec<_> = out.split_inclusive("\x1b\\").collect();
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces[0].starts_with("\x1b_Ga=T,f=100,c=1,r=1,C=1,q=2,m=1;AAAA"));
+        assert_eq!(pieces[1], "\x1b_Gm=0;AAAA\x1b\\");
+    }
+
+    #[test]
+    fn test_iterm2() {
+        assert_eq!(
+            iterm2(b"PNG", 4, 2),
+            b"\x1b]1337;File=inline=1;size=3;width=4;height=2;preserveAspectRatio=1:UE5H\x07"
+        );
+    }
+
+    #[test]
+    fn test_sixel() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let clear = [0, 0, 0, 0];
+        let pixels = |width, height, pixels: &[[u8; 4]]| Pixels {
+            width,
+            height,
+            rgba: pixels.concat(),
+        };
+
+        // red on top of a transparent pixel, the first sixel bit
+        assert_eq!(
+            sixel(&pixels(1, 2, &[red, clear])),
+            b"\x1bP0;1;0q\"1;1;1;2#180;2;100;0;0#180@-\x1b\\"
+        );
+
+        // runs shortened, colors after the first starting from the band's start again, and
+        // seven rows take two bands
+        let mut image = vec![red; 5];
+        image.extend([clear, clear, clear, clear, blue]);
+        image.extend([clear; 20]);
+        image.extend([red; 5]);
+        assert_eq!(
+            String::from_utf8(sixel(&pixels(5, 7, &image))).unwrap(),
+            "\x1bP0;1;0q\"1;1;5;7#5;2;0;0;100#180;2;100;0;0\
+             #5!4?A$#180!5@-#180!5@-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_image_logo() -> TestResult {
+        let png = png(16, 32, png::ColorType::Rgba, &[255; 16 * 32 * 4]);
+        let logo = ImageLogo::new(&png, GraphicsProtocol::Sixel, (8, 16))?;
+        assert_eq!(logo.size(), (2, 2));
+        assert!(logo.sequence.starts_with(b"\x1bP0;1;0q\"1;1;16;32"));
+
+        let logo = ImageLogo::new(&png, GraphicsProtocol::Kitty, (8, 16))?;
+        let mut out = vec![];
+        logo.draw(&mut out, Position::new(3, 1))?;
+        // the cursor is saved, moved and put back around the image
+        assert!(out.starts_with(b"\x1b7\x1b[2;4H\x1b_Ga=T"));
+        assert!(out.ends_with(b"\x1b\\\x1b8"));
+        Ok(())
+    }
+}
@@ -0,0 +1,602 @@
+//! The art drawn above the graph and how it's colored.
+//!
+//! Logos are text files. Plain ones are drawn in the theme's logo color, `.toml` ones are
+//! the colored-art format and bring colors of their own:
+//!
+//! ```toml
+//! # one color per `${cN}` marker, or the stops of a gradient
+//! colors = ["#fff1a4", "light-magenta"]
+//! # "vertical" or "horizontal" to blend the colors over the art instead
+//! # gradient = "vertical"
+//! # os-release IDs `--logo auto` picks this logo for
+//! # ids = ["arch"]
+//! art = '''
+//! ${c1}so${c2}-sysinfo
+//! {copyright}
+//! '''
+//! ```
+//!
+//! A `${cN}` marker colors everything after it with the Nth color, across lines, until the
+//! next one. `${c0}` goes back to the theme's color. In either kind of file, `{cr_year}` is
+//! replaced with the current year and `{copyright}` with the copyright notice, which keeps
+//! to the theme's body color.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Local;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span, Text};
+use serde::Deserialize;
+use std::ops::Range;
+use std::path::Path;
+use unicode_width::UnicodeWidthChar;
+
+use crate::theme::{Theme, ThemeColor};
+
+pub(crate) mod image;
+
+/// Logos that come built in, by name, the first one being the default.
+const BUILTIN_LOGOS: &[(&str, &str)] = &[
+    ("shadow", include_str!("shadow.toml")),
+    ("graffiti", include_str!("graffiti.toml")),
+    ("sunset", include_str!("sunset.toml")),
+    ("neon", include_str!("neon.toml")),
+    ("duotone", include_str!("duotone.toml")),
+];
+
+/// Logos of operating systems, which `--logo auto` picks from by their `ids`.
+const DISTRO_LOGOS: &[(&str, &str)] = &[
+    ("alpine", include_str!("distros/alpine.toml")),
+    ("arch", include_str!("distros/arch.toml")),
+    ("debian", include_str!("distros/debian.toml")),
+    ("fedora", include_str!("distros/fedora.toml")),
+    ("gentoo", include_str!("distros/gentoo.toml")),
+    ("linuxmint", include_str!("distros/linuxmint.toml")),
+    ("manjaro", include_str!("distros/manjaro.toml")),
+    ("nixos", include_str!("distros/nixos.toml")),
+    ("opensuse", include_str!("distros/opensuse.toml")),
+    ("ubuntu", include_str!("distros/ubuntu.toml")),
+    ("void", include_str!("distros/void.toml")),
+];
+
+/// The name of the logo drawn when the config doesn't pick one.
+pub(crate) const DEFAULT_LOGO: &str = BUILTIN_LOGOS[0].0;
+
+/// Logo name that picks the operating system's logo, or the default one if there's none.
+pub(crate) const AUTO_LOGO: &str = "auto";
+
+/// Every built-in logo, in the order they're cycled through.
+pub(crate) fn builtin() -> Vec<Logo> {
+    parse_all(BUILTIN_LOGOS)
+}
+
+/// Every built-in operating system logo.
+pub(crate) fn distros() -> Vec<Logo> {
+    parse_all(DISTRO_LOGOS)
+}
+
+fn parse_all(sources: &[(&str, &str)]) -> Vec<Logo> {
+    sources
+        .iter()
+        .map(|(name, source)| {
+            Logo::parse(name, source).expect("built-in logos are tested to parse")
+        })
+        .collect()
+}
+
+/// The logo among `logos` for the first of `os_ids` any of them is for, `os_ids` being the
+/// os-release `ID` followed by its `ID_LIKE`.
+pub(crate) fn for_os(logos: &[Logo], os_ids: &[String]) -> Option<usize> {
+    os_ids
+        .iter()
+        .find_map(|id| logos.iter().position(|logo| logo.ids.contains(id)))
+}
+
+/// Adds the logos in `dir` to `logos`, in place of any built-in ones with the same name.
+pub(crate) fn load_dir(dir: &Path, logos: &mut Vec<Logo>) -> Result<()> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read logos directory {}", dir.display()))?;
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let logo = Logo::load(&path)?;
+        match logos.iter_mut().find(|known| known.name == logo.name) {
+            Some(known) => *known = logo,
+            None => logos.push(logo),
+        }
+    }
+    Ok(())
+}
+
+/// A `.toml` logo file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LogoFile {
+    art: String,
+    #[serde(default)]
+    colors: Vec<ThemeColor>,
+    gradient: Option<GradientDirection>,
+    #[serde(default)]
+    ids: Vec<String>,
+}
+
+/// Placeholder for the copyright notice in logo art.
+const COPYRIGHT: &str = "{copyright}";
+
+/// Put wherever a logo's art has `{copyright}`.
+fn copyright() -> String {
+    expand_year("(C) Solaara's Network {cr_year}")
+}
+
+fn expand_year(art: &str) -> String {
+    art.replace("{cr_year}", &format!("{:0>4}", Local::now().format("%Y")))
+}
+
+/// How a logo's art is colored. The copyright notice is left out of it and drawn in the
+/// theme's body color.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LogoColors {
+    /// The theme's logo color all over.
+    Theme,
+    /// Blends from the first of `stops` to the last, spread evenly over the art.
+    Gradient {
+        direction: GradientDirection,
+        stops: Vec<Color>,
+    },
+    /// Colors each character by the digit at the same place in `map`, `1` being the first of
+    /// `palette`. Anything else in the map, or past its end, keeps the theme's logo color.
+    Map {
+        palette: Vec<Color>,
+        map: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum GradientDirection {
+    /// From the top line to the bottom one.
+    Vertical,
+    /// From the first column to the last.
+    Horizontal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Logo {
+    /// What `--logo` calls it, the file name without its extension for logo files.
+    pub name: String,
+    /// os-release IDs of the operating systems this is the logo of.
+    pub ids: Vec<String>,
+    lines: Vec<String>,
+    /// Line and characters the copyright notice took the place of `{copyright}` at.
+    copyright: Option<(usize, Range<usize>)>,
+    colors: LogoColors,
+    width: usize,
+}
+
+impl Logo {
+    /// Logo drawing `art`, with its placeholders filled in.
+    pub fn new(name: &str, art: &str, mut colors: LogoColors) -> Self {
+        let mut copyright = None;
+        let lines: Vec<String> = expand_year(art)
+            .lines()
+            .enumerate()
+            .map(|(row, line)| match line.split_once(COPYRIGHT) {
+                Some((before, after)) if copyright.is_none() => {
+                    let notice = self::copyright();
+                    let start = before.chars().count();
+                    let end = start + notice.chars().count();
+                    copyright = Some((row, start..end));
+                    // keep the map lined up with the characters after the notice
+                    if let LogoColors::Map { map, .. } = &mut colors {
+                        if let Some(colors) = map.get_mut(row) {
+                            *colors = colors
+                                .chars()
+                                .take(start)
+                                .chain(std::iter::repeat('0').take(end - start))
+                                .chain(colors.chars().skip(start + COPYRIGHT.len()))
+                                .collect();
+                        }
+                    }
+                    format!("{before}{notice}{after}")
+                }
+                _ => line.to_string(),
+            })
+            .collect();
+        let width = lines.iter().map(|line| line_width(line)).max().unwrap_or(0);
+
+        Self {
+            name: name.to_string(),
+            ids: vec![],
+            lines,
+            copyright,
+            colors,
+            width,
+        }
+    }
+
+    /// Reads the logo file at `path`, in the colored-art format if it's a `.toml` file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| anyhow!("Logo file {} has no usable name.", path.display()))?;
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read logo file {}", path.display()))?;
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            Self::parse(name, &source)
+                .with_context(|| format!("Failed to parse logo file {}", path.display()))
+        } else {
+            Ok(Self::new(name, &source, LogoColors::Theme))
+        }
+    }
+
+    /// Reads a logo in the colored-art format.
+    pub fn parse(name: &str, source: &str) -> Result<Self> {
+        let file: LogoFile = toml::from_str(source)?;
+        let palette: Vec<Color> = file.colors.iter().map(|color| color.0).collect();
+        // the year first, so the markers' map lines up with what's drawn
+        let (art, map) = color_markers(&expand_year(&file.art))?;
+        if let Some(marker) = map.iter().flat_map(|line| line.chars()).max() {
+            let used = marker.to_digit(10).unwrap_or(0) as usize;
+            if file.gradient.is_none() && used > palette.len() {
+                bail!(
+                    "Logo uses color {used} but only has {} colors.",
+                    palette.len()
+                );
+            }
+        }
+
+        let colors = match file.gradient {
+            Some(direction) => LogoColors::Gradient {
+                direction,
+                stops: palette,
+            },
+            None if palette.is_empty() => LogoColors::Theme,
+            None => LogoColors::Map { palette, map },
+        };
+        Ok(Self {
+            ids: file.ids,
+            ..Self::new(name, &art, colors)
+        })
+    }
+
+    /// Columns the widest line takes up.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The art colored for `theme`, with runs of the same color in one span.
+    pub fn text(&self, theme: &Theme) -> Text<'static> {
+        let lines = self.lines.iter().enumerate().map(|(row, line)| {
+            let mut spans: Vec<Span> = vec![];
+            let mut column = 0;
+            for (idx, c) in line.chars().enumerate() {
+                let color = match &self.copyright {
+                    Some((copyright_row, chars))
+                        if *copyright_row == row && chars.contains(&idx) =>
+                    {
+                        theme.body
+                    }
+                    _ => self.color_at(row, idx, column, theme.logo),
+                };
+                column += c.width().unwrap_or(0);
+
+                match spans.last_mut() {
+                    Some(span) if span.style.fg == Some(color) => span.content.to_mut().push(c),
+                    _ => spans.push(Span::styled(c.to_string(), Style::new().fg(color))),
+                }
+            }
+            Line::from(spans)
+        });
+        Text::from(lines.collect::<Vec<_>>())
+    }
+
+    /// Color of the character at `idx` in line `row`, which starts at `column`.
+    fn color_at(&self, row: usize, idx: usize, column: usize, default: Color) -> Color {
+        match &self.colors {
+            LogoColors::Theme => default,
+            LogoColors::Gradient { direction, stops } => {
+                let (position, length) = match direction {
+                    GradientDirection::Vertical => (row, self.height()),
+                    GradientDirection::Horizontal => (column, self.width),
+                };
+                let fraction = position as f32 / length.saturating_sub(1).max(1) as f32;
+                gradient(stops, fraction).unwrap_or(default)
+            }
+            LogoColors::Map { palette, map } => map
+                .get(row)
+                .and_then(|line| line.chars().nth(idx)?.to_digit(10))
+                .and_then(|digit| palette.get((digit as usize).checked_sub(1)?))
+                .copied()
+                .unwrap_or(default),
+        }
+    }
+}
+
+/// Takes the `${cN}` markers out of `art`, returning what's left and a map with the color
+/// number of each character.
+fn color_markers(art: &str) -> Result<(String, Vec<String>)> {
+    let mut current = '0';
+    let mut lines = vec![];
+    let mut map = vec![];
+    for line in art.lines() {
+        let mut text = String::new();
+        let mut colors = String::new();
+        let mut rest = line;
+        while let Some(start) = rest.find("${c") {
+            let (before, marker) = rest.split_at(start);
+            text.push_str(before);
+            colors.extend(std::iter::repeat(current).take(before.chars().count()));
+
+            let mut marker_chars = marker["${c".len()..].chars();
+            current = match (marker_chars.next(), marker_chars.next()) {
+                (Some(digit @ '0'..='9'), Some('}')) => digit,
+                _ => bail!("Unknown color marker in `{line}`, they go from ${{c0}} to ${{c9}}."),
+            };
+            rest = &marker["${c0}".len()..];
+        }
+        text.push_str(rest);
+        colors.extend(std::iter::repeat(current).take(rest.chars().count()));
+        lines.push(text);
+        map.push(colors);
+    }
+    Ok((lines.join("\n"), map))
+}
+
+fn line_width(line: &str) -> usize {
+    line.chars().map(|c| c.width().unwrap_or(0)).sum()
+}
+
+/// The color `fraction` of the way through `stops`. Only RGB colors blend, anything else
+/// switches over halfway to the next stop.
+fn gradient(stops: &[Color], fraction: f32) -> Option<Color> {
+    let last = stops.len().checked_sub(1)?;
+    let position = fraction.clamp(0.0, 1.0) * last as f32;
+    let from = (position.floor() as usize).min(last);
+    let to = (from + 1).min(last);
+    let blend = position - from as f32;
+
+    Some(match (stops[from], stops[to]) {
+        (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+            let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * blend).round() as u8;
+            Color::Rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2))
+        }
+        (from, _) if blend < 0.5 => from,
+        (_, to) => to,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testresult::TestResult;
+
+    fn colors(text: &Text) -> Vec<Vec<(String, Option<Color>)>> {
+        text.lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| (span.content.to_string(), span.style.fg))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_logos() {
+        let logos = builtin();
+        assert_eq!(logos.len(), BUILTIN_LOGOS.len());
+        assert_eq!(logos[0].name, DEFAULT_LOGO);
+        assert_eq!((logos[0].width(), logos[0].height()), (80, 16));
+        assert_eq!((logos[1].width(), logos[1].height()), (52, 6));
+        // the copyright notice is in every one of them
+        assert!(logos.iter().all(|logo| logo.copyright.is_some()));
+    }
+
+    #[test]
+    fn test_distro_logos() {
+        let logos = distros();
+        assert_eq!(logos.len(), DISTRO_LOGOS.len());
+        for logo in &logos {
+            assert!(
+                logo.ids.contains(&logo.name),
+                "{} isn't for itself",
+                logo.name
+            );
+            assert!(
+                logo.width() <= 20 && logo.height() <= 8,
+                "{} is too big",
+                logo.name
+            );
+            // every character is in the distro's colors
+            let text = logo.text(&Theme::single(Color::Reset));
+            let spans = text.lines.iter().flat_map(|line| &line.spans);
+            assert!(spans
+                .clone()
+                .all(|span| span.style.fg != Some(Color::Reset)));
+        }
+
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        let name = |idx: Option<usize>| idx.map(|idx| logos[idx].name.as_str());
+        assert_eq!(name(for_os(&logos, &ids(&["arch"]))), Some("arch"));
+        // Mint's own logo before the Ubuntu one it's like
+        let mint = ids(&["linuxmint", "ubuntu", "debian"]);
+        assert_eq!(name(for_os(&logos, &mint)), Some("linuxmint"));
+        assert_eq!(
+            name(for_os(&logos, &ids(&["pop", "ubuntu"]))),
+            Some("ubuntu")
+        );
+        assert_eq!(name(for_os(&logos, &ids(&["haiku"]))), None);
+    }
+
+    #[test]
+    fn test_parse_colored_art() -> TestResult {
+        let logo = Logo::parse(
+            "test",
+            r##"
+            colors = ["#ff0000", "blue"]
+            art = '''
+${c1}ab${c2}c
+d${c0}e {cr_year}
+'''
+            "##,
+        )?;
+        let year = Local::now().format("%Y").to_string();
+        assert_eq!(logo.lines, vec!["abc".to_string(), format!("de {year}")]);
+        assert_eq!(
+            logo.colors,
+            LogoColors::Map {
+                palette: vec![Color::Rgb(255, 0, 0), Color::Blue],
+                map: vec!["112".to_string(), "2000000".to_string()],
+            }
+        );
+
+        let error = Logo::parse("test", "colors = [\"red\"]\nart = \"${c2}x\"").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Logo uses color 2 but only has 1 colors."
+        );
+        assert!(Logo::parse("test", "art = \"${cx}\"").is_err());
+        assert!(Logo::parse("test", "art = \"x\"\nfont = \"big\"").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wide_characters() {
+        let logo = Logo::new("test", "全角\nab", LogoColors::Theme);
+        assert_eq!((logo.width(), logo.height()), (4, 2));
+    }
+
+    #[test]
+    fn test_load_dir() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("shadow.txt"), "mine")?;
+        std::fs::write(
+            dir.path().join("tiny.toml"),
+            "colors = [\"red\"]\ngradient = \"vertical\"\nart = \"x\"",
+        )?;
+
+        let mut logos = builtin();
+        load_dir(dir.path(), &mut logos)?;
+        assert_eq!(logos.len(), BUILTIN_LOGOS.len() + 1);
+        assert_eq!(logos[0].name, "shadow");
+        assert_eq!(logos[0].lines, vec!["mine".to_string()]);
+        assert_eq!(logos.last().map(|logo| logo.name.as_str()), Some("tiny"));
+
+        std::fs::write(dir.path().join("broken.toml"), "art = ")?;
+        let error = load_dir(dir.path(), &mut logos).unwrap_err();
+        assert!(error.to_string().starts_with("Failed to parse logo file"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_copyright_keeps_its_color() {
+        let theme = Theme {
+            body: Color::Blue,
+            ..Theme::single(Color::Red)
+        };
+        let logo = Logo::new("test", "##\n# {copyright} #", LogoColors::Theme);
+        let text = logo.text(&theme);
+
+        assert_eq!(
+            colors(&text)[1],
+            vec![
+                ("# ".to_string(), Some(Color::Red)),
+                (copyright(), Some(Color::Blue)),
+                (" #".to_string(), Some(Color::Red)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gradients() {
+        let stops = vec![Color::Rgb(0, 0, 0), Color::Rgb(200, 100, 0)];
+        let theme = Theme::single(Color::Red);
+
+        let logo = Logo::new(
+            "test",
+            "#\n#\n#",
+            LogoColors::Gradient {
+                direction: GradientDirection::Vertical,
+                stops: stops.clone(),
+            },
+        );
+        let fg: Vec<_> = colors(&logo.text(&theme))
+            .concat()
+            .into_iter()
+            .map(|(_, fg)| fg)
+            .collect();
+        assert_eq!(
+            fg,
+            vec![
+                Some(Color::Rgb(0, 0, 0)),
+                Some(Color::Rgb(100, 50, 0)),
+                Some(Color::Rgb(200, 100, 0)),
+            ]
+        );
+
+        // wide characters take up two columns of the gradient
+        let logo = Logo::new(
+            "test",
+            "#全#",
+            LogoColors::Gradient {
+                direction: GradientDirection::Horizontal,
+                stops,
+            },
+        );
+        let fg: Vec<_> = colors(&logo.text(&theme))[0]
+            .iter()
+            .map(|(_, fg)| *fg)
+            .collect();
+        assert_eq!(
+            fg,
+            vec![
+                Some(Color::Rgb(0, 0, 0)),
+                Some(Color::Rgb(67, 33, 0)),
+                Some(Color::Rgb(200, 100, 0)),
+            ]
+        );
+
+        // named colors can't blend
+        assert_eq!(gradient(&[Color::Red, Color::Blue], 0.4), Some(Color::Red));
+        assert_eq!(gradient(&[Color::Red, Color::Blue], 0.6), Some(Color::Blue));
+        assert_eq!(gradient(&[], 0.5), None);
+    }
+
+    #[test]
+    fn test_color_map() {
+        let logo = Logo::new(
+            "test",
+            "abcd\nefgh",
+            LogoColors::Map {
+                palette: vec![Color::Green, Color::Blue],
+                map: vec!["1122".to_string(), "29".to_string()],
+            },
+        );
+        assert_eq!(
+            colors(&logo.text(&Theme::single(Color::Red))),
+            vec![
+                vec![
+                    ("ab".to_string(), Some(Color::Green)),
+                    ("cd".to_string(), Some(Color::Blue)),
+                ],
+                vec![
+                    ("e".to_string(), Some(Color::Blue)),
+                    ("fgh".to_string(), Some(Color::Red)),
+                ],
+            ]
+        );
+    }
+}
@@ -3,6 +3,7 @@ use clap::Parser;
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     crossterm::{
+        event::{DisableMouseCapture, EnableMouseCapture},
         terminal::{
             disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
         },
@@ -10,12 +11,19 @@ use ratatui::{
     },
     Terminal,
 };
-use std::io::stdout;
+use std::io::{stdout, Write};
 
 mod app;
 mod args;
 mod collection;
+mod color_support;
+mod config;
+mod history;
+mod keymap;
+mod layout;
 mod logos;
+mod output;
+mod theme;
 
 pub fn init_panic_hook() {
     let original_hook = std::panic::take_hook();
@@ -26,14 +34,16 @@ pub fn init_panic_hook() {
     }));
 }
 
-pub fn init_tui() -> std::io::Result<Terminal<impl Backend>> {
+pub fn init_tui() -> std::io::Result<Terminal<impl Backend + Write>> {
     enable_raw_mode()?;
     stdout().execute(SetTitle("so-sysinfo"))?;
     stdout().execute(EnterAlternateScreen)?;
+    stdout().execute(EnableMouseCapture)?;
     Terminal::new(CrosstermBackend::new(stdout()))
 }
 
 pub fn restore_tui() -> std::io::Result<()> {
+    stdout().execute(DisableMouseCapture)?;
     stdout().execute(LeaveAlternateScreen)?;
     disable_raw_mode()?;
     Ok(())
@@ -43,12 +53,34 @@ fn main() -> Result<()> {
     init_panic_hook();
     let args = args::Args::parse();
 
-    let mut terminal = init_tui()?;
-    terminal.clear()?;
+    if args.print_default_config {
+        print!("{}", config::DEFAULT_CONFIG);
+        return Ok(());
+    }
+    let config = config::Config::load(&args)?;
+    let ctx = args.collection_context(config.visual_toggles());
 
-    app::app(terminal, args)?;
+    if args.diagnose {
+        return output::diagnose::print(&ctx, &config);
+    }
+    if args.json {
+        return output::json::print(&ctx, &config);
+    }
+    if args.print {
+        return output::print::print(&ctx, &config, args.width);
+    }
 
+    // anything that can fail before drawing does so while the terminal is still untouched
+    let app = app::App::new(&ctx, &config)?;
+
+    let result = init_tui()
+        .map_err(anyhow::Error::from)
+        .and_then(|mut terminal| {
+            terminal.clear()?;
+            app.run(terminal)
+        });
+    // put the terminal back however the app ended, before any error is printed
     restore_tui()?;
 
-    Ok(())
+    result
 }
@@ -0,0 +1,24 @@
+use anyhow::Result;
+use std::io::Write;
+use std::time::Instant;
+
+use crate::collection::diagnostics::Diagnostic;
+use crate::collection::system_components::{Hostname, SystemComponent};
+use crate::collection::{collect_components, CollectionContext};
+use crate::config::Config;
+
+/// Runs every collector and lists how each one went, one collector per line.
+pub(crate) fn print(ctx: &CollectionContext, config: &Config) -> Result<()> {
+    let start = Instant::now();
+    let hostname = Hostname::collect_info(ctx);
+    let hostname = Diagnostic::new("hostname", &hostname, start.elapsed());
+
+    let reports = collect_components(ctx, &config.enabled_components());
+
+    let mut stdout = std::io::stdout().lock();
+    for diagnostic in std::iter::once(hostname).chain(reports.iter().map(Diagnostic::from)) {
+        writeln!(stdout, "{diagnostic}")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Write;
+
+use crate::collection::info::{ComponentInfo, HostnameInfo};
+use crate::collection::system_components::{Hostname, SystemComponent};
+use crate::collection::{
+    collect_components, CollectionContext, ComponentReport, SystemComponentKind,
+};
+use crate::config::Config;
+
+/// Bumped whenever a field is removed or changes meaning; adding fields keeps the version.
+const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+struct Document {
+    schema_version: u32,
+    generator: &'static str,
+    version: &'static str,
+    hostname: Option<String>,
+    components: Vec<ComponentEntry>,
+    errors: Vec<ErrorEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct ComponentEntry {
+    kind: SystemComponentKind,
+    title: &'static str,
+    summary: String,
+    info: ComponentInfo,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorEntry {
+    component: &'static str,
+    message: String,
+}
+
+impl Document {
+    fn new(hostname: Result<Vec<HostnameInfo>>, components: Vec<ComponentReport>) -> Self {
+        let mut errors = Vec::new();
+
+        let hostname = match hostname {
+            Ok(hostname) => hostname.into_iter().next().map(|info| info.hostname),
+            Err(e) => {
+                errors.push(ErrorEntry {
+                    component: "hostname",
+                    message: format!("{e:#}"),
+                });
+                None
+            }
+        };
+
+        let components = components
+            .into_iter()
+            .flat_map(|ComponentReport { kind, result, .. }| match result {
+                Ok(records) => records
+                    .into_iter()
+                    .map(|info| ComponentEntry {
+                        kind,
+                        title: kind.title(),
+                        summary: info.to_string(),
+                        info,
+                    })
+                    .collect(),
+                Err(e) => {
+                    errors.push(ErrorEntry {
+                        component: kind.id(),
+                        message: format!("{e:#}"),
+                    });
+                    vec![]
+                }
+            })
+            .collect();
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            generator: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            hostname,
+            components,
+            errors,
+        }
+    }
+}
+
+/// Collects everything and writes it to stdout as a single JSON document.
+pub(crate) fn print(ctx: &CollectionContext, config: &Config) -> Result<()> {
+    let document = Document::new(
+        Hostname::collect_info(ctx),
+        collect_components(ctx, &config.enabled_components()),
+    );
+
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &document)?;
+    writeln!(stdout)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::info::BoardModelInfo;
+    use std::time::Duration;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_document_shape() -> TestResult {
+        let document = Document::new(
+            Ok(vec![HostnameInfo {
+                hostname: "solaara-box".to_string(),
+            }]),
+            vec![
+                ComponentReport {
+                    kind: SystemComponentKind::BoardModel,
+                    result: Ok(vec![ComponentInfo::BoardModel(BoardModelInfo {
+                        name: "Generic".to_string(),
+                        vendor: None,
+                        version: None,
+                    })]),
+                    elapsed: Duration::ZERO,
+                },
+                ComponentReport {
+                    kind: SystemComponentKind::Gpu,
+                    result: Err(anyhow::Error::msg("unimplemented")),
+                    elapsed: Duration::ZERO,
+                },
+            ],
+        );
+
+        let value = serde_json::to_value(document)?;
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["hostname"], "solaara-box");
+        assert_eq!(value["components"][0]["kind"], "board_model");
+        assert_eq!(value["components"][0]["summary"], "Generic");
+        assert_eq!(value["components"][0]["info"]["name"], "Generic");
+        assert_eq!(value["errors"][0]["component"], "gpu");
+        assert_eq!(value["errors"][0]["message"], "unimplemented");
+        Ok(())
+    }
+}
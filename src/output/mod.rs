@@ -0,0 +1,5 @@
+//! Non-interactive output modes that bypass the TUI.
+
+pub(crate) mod diagnose;
+pub(crate) mod json;
+pub(crate) mod print;
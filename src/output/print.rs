@@ -0,0 +1,275 @@
+use anyhow::Result;
+use ratatui::{
+    backend::TestBackend,
+    buffer::{Buffer, Cell},
+    crossterm::{
+        queue,
+        style::{Attribute, Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
+        terminal,
+    },
+    style::{Color, Modifier, Style},
+    Terminal,
+};
+use std::io::{IsTerminal, Write};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::{draw, AppState};
+use crate::collection::{collect, collect_components, hostname, CollectionContext};
+use crate::color_support::ColorSupport;
+use crate::config::Config;
+use crate::layout::SystemGraph;
+
+/// Used when neither `--width` nor the terminal tell us how wide to render.
+const FALLBACK_WIDTH: u16 = 80;
+
+/// Renders the logo and system graph once and writes it to stdout, `width` columns wide.
+pub(crate) fn print(ctx: &CollectionContext, config: &Config, width: Option<u16>) -> Result<()> {
+    let width = width
+        .or_else(|| terminal::size().ok().map(|(width, _)| width))
+        .filter(|width| *width > 0)
+        .unwrap_or(FALLBACK_WIDTH);
+    let color_support = ColorSupport::detect(config.color, std::io::stdout().is_terminal());
+    let buffer = render(ctx, config, width, color_support)?;
+
+    let mut stdout = std::io::stdout().lock();
+    write_buffer(&mut stdout, &buffer)?;
+    stdout.flush()?;
+
+    Ok(())
+}
+
+/// Draws the logo and system graph into a buffer `width` columns wide and as high as they
+/// need.
+fn render(
+    ctx: &CollectionContext,
+    config: &Config,
+    width: u16,
+    color_support: ColorSupport,
+) -> Result<Buffer> {
+    let reports = collect_components(ctx, &config.enabled_components());
+    let (nodes, links) = collect(&reports, config)?;
+    let graph = SystemGraph::new(nodes, links);
+    let hostname = hostname(ctx);
+
+    let app_state = AppState::from_config(config, ctx)?;
+
+    // the logo, the hostname frame's border and padding, then the graph itself
+    let height = app_state.logo_height() + 3 + graph.height_for_width(width.saturating_sub(2));
+
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|frame| {
+        draw(frame, &app_state, &graph, &hostname, None);
+        let area = frame.area();
+        match color_support {
+            // plain text without any escape codes
+            ColorSupport::None => frame.buffer_mut().set_style(area, Style::reset()),
+            _ => color_support.adapt_buffer(frame.buffer_mut()),
+        }
+    })?;
+
+    Ok(terminal.backend().buffer().clone())
+}
+
+/// Writes `buffer` as lines of text with ANSI styling, dropping trailing blank cells and rows.
+fn write_buffer(writer: &mut impl Write, buffer: &Buffer) -> std::io::Result<()> {
+    let width = buffer.area.width as usize;
+    let rows: Vec<_> = buffer.content.chunks(width).collect();
+
+    // a space only shows its background and modifiers, the foreground color doesn't matter
+    let is_blank = |cell: &Cell| {
+        cell.skip || cell.symbol() == " " && cell.bg == Color::Reset && cell.modifier.is_empty()
+    };
+    let last_row = rows
+        .iter()
+        .rposition(|row| !row.iter().all(is_blank))
+        .map_or(0, |idx| idx + 1);
+
+    for row in &rows[..last_row] {
+        let row_end = row
+            .iter()
+            .rposition(|cell| !is_blank(cell))
+            .map_or(0, |idx| idx + 1);
+
+        let mut current_style = PLAIN;
+        let mut skip = 0;
+        for cell in &row[..row_end] {
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            if cell.skip {
+                continue;
+            }
+
+            // spaces look the same under any foreground, so avoid switching styles for them
+            let restyle = cell_style(cell) != current_style
+                && !(is_blank(cell)
+                    && current_style.1 == Color::Reset
+                    && current_style.2.is_empty());
+            if restyle {
+                queue!(writer, SetAttribute(Attribute::Reset))?;
+                queue_modifiers(writer, cell.modifier)?;
+                queue!(
+                    writer,
+                    SetForegroundColor(cell.fg.into()),
+                    SetBackgroundColor(cell.bg.into())
+                )?;
+                current_style = cell_style(cell);
+            }
+
+            queue!(writer, Print(cell.symbol()))?;
+            // wide symbols cover the cells after them
+            skip = cell.symbol().width().saturating_sub(1);
+        }
+
+        if current_style != PLAIN {
+            queue!(writer, SetAttribute(Attribute::Reset))?;
+        }
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Foreground, background and modifiers of an unstyled cell.
+const PLAIN: (Color, Color, Modifier) = (Color::Reset, Color::Reset, Modifier::empty());
+
+fn cell_style(cell: &Cell) -> (Color, Color, Modifier) {
+    (cell.fg, cell.bg, cell.modifier)
+}
+
+fn queue_modifiers(writer: &mut impl Write, modifier: Modifier) -> std::io::Result<()> {
+    for (flag, attribute) in [
+        (Modifier::BOLD, Attribute::Bold),
+        (Modifier::DIM, Attribute::Dim),
+        (Modifier::ITALIC, Attribute::Italic),
+        (Modifier::UNDERLINED, Attribute::Underlined),
+        (Modifier::SLOW_BLINK, Attribute::SlowBlink),
+        (Modifier::RAPID_BLINK, Attribute::RapidBlink),
+        (Modifier::REVERSED, Attribute::Reverse),
+        (Modifier::HIDDEN, Attribute::Hidden),
+        (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+    ] {
+        if modifier.contains(flag) {
+            queue!(writer, SetAttribute(attribute))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::fixtures::{fixture, offline};
+    use crate::collection::info::{
+        BoardModelInfo, ComponentInfo, CpuInfo, HostnameInfo, NetInfo, NetLink, OsInfo,
+    };
+    use crate::collection::{ComponentReport, SystemComponentKind};
+    use ratatui::layout::Rect;
+    use std::time::Duration;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_without_hostname() -> TestResult {
+        let root = fixture(&[]);
+        let buffer = render(&offline(&root), &Config::default(), 80, ColorSupport::None)?;
+
+        let mut out = Vec::new();
+        write_buffer(&mut out, &buffer)?;
+        assert!(String::from_utf8(out)?.contains("─ unknown ─"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_links_share_one_node() -> TestResult {
+        // a plain VM's links, which used to get a node each
+        let link = |name: &str| NetLink {
+            name: name.to_string(),
+            kind: "virtual".to_string(),
+            is_virtual: true,
+            state: "down".to_string(),
+            mac: None,
+            ipv4: vec![],
+            ipv6: vec![],
+            speed_mbps: None,
+        };
+        let report = |kind, info| ComponentReport {
+            kind,
+            result: Ok(vec![info]),
+            elapsed: Duration::ZERO,
+        };
+        let reports = [
+            report(
+                SystemComponentKind::Cpu,
+                ComponentInfo::Cpu(CpuInfo {
+                    model: "Intel Xeon".to_string(),
+                    threads: 1,
+                    ..Default::default()
+                }),
+            ),
+            report(
+                SystemComponentKind::Net,
+                ComponentInfo::Net(NetInfo {
+                    links: ["eth0", "ifb0", "ifb1", "lo"].map(link).to_vec(),
+                }),
+            ),
+            report(
+                SystemComponentKind::BoardModel,
+                ComponentInfo::BoardModel(BoardModelInfo {
+                    name: "Generic".to_string(),
+                    vendor: None,
+                    version: None,
+                }),
+            ),
+            report(
+                SystemComponentKind::OperatingSystem,
+                ComponentInfo::OperatingSystem(OsInfo {
+                    name: "Debian GNU/Linux 12 (bookworm)".to_string(),
+                    ..Default::default()
+                }),
+            ),
+        ];
+        let config = Config::default();
+        let (nodes, links) = collect(&reports, &config)?;
+        let graph = SystemGraph::new(nodes, links);
+
+        let app_state = AppState::new(config.logos(&[])?, config.themes());
+        let hostname = HostnameInfo {
+            hostname: "vm".to_string(),
+        };
+        let (width, height) = (100, 40);
+        let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+        terminal.draw(|frame| {
+            draw(frame, &app_state, &graph, &hostname, None);
+            let area = frame.area();
+            frame.buffer_mut().set_style(area, Style::reset());
+        })?;
+
+        let mut out = Vec::new();
+        write_buffer(&mut out, terminal.backend().buffer())?;
+        let text = String::from_utf8(out)?;
+        assert_eq!(text.matches("[ Net ]").count(), 1);
+        for name in ["eth0", "ifb0", "ifb1", "lo"] {
+            assert!(text.contains(&format!("│ {name} down (virtual)")));
+        }
+        // edges tui-nodes couldn't route end in greek letters
+        assert!(!text.contains(['α', 'β']));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_buffer_trims_and_styles() -> TestResult {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 10, 4));
+        buffer.set_string(0, 0, "ab", Style::new().fg(Color::Red));
+        buffer.set_string(0, 1, "plain", Style::reset());
+
+        let mut out = Vec::new();
+        write_buffer(&mut out, &buffer)?;
+
+        assert_eq!(
+            String::from_utf8(out)?,
+            "\u{1b}[0m\u{1b}[38;5;1m\u{1b}[49mab\u{1b}[0m\nplain\n"
+        );
+        Ok(())
+    }
+}
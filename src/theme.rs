@@ -0,0 +1,276 @@
+//! Colors each part of the screen is drawn in, from a built-in theme with the config file's
+//! colors on top.
+
+use anyhow::{anyhow, Result};
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// What the logo was first drawn in, also usable by name as `solaara-gold`.
+const SOLAARA_GOLD: Color = Color::Rgb(255, 241, 164);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Theme {
+    pub logo: Color,
+    /// Node borders, the hostname frame and popup borders.
+    pub border: Color,
+    /// Lines connecting nodes.
+    pub edge: Color,
+    /// Node and popup titles.
+    pub title: Color,
+    /// Text inside nodes, popups and the footer.
+    pub body: Color,
+    pub hostname: Color,
+    /// Failed collectors and the window being too small.
+    pub error: Color,
+}
+
+impl Theme {
+    /// Everything in `color`, except errors which stay red.
+    pub const fn single(color: Color) -> Self {
+        Self {
+            logo: color,
+            border: color,
+            edge: color,
+            title: color,
+            body: color,
+            hostname: color,
+            error: Color::Red,
+        }
+    }
+
+    /// This theme with whatever `colors` sets in place of its own.
+    pub fn with_colors(self, colors: &ThemeColors) -> Self {
+        let pick = |color: Option<ThemeColor>, default: Color| color.map_or(default, |c| c.0);
+        Self {
+            logo: pick(colors.logo, self.logo),
+            border: pick(colors.border, self.border),
+            edge: pick(colors.edge, self.edge),
+            title: pick(colors.title, self.title),
+            body: pick(colors.body, self.body),
+            hostname: pick(colors.hostname, self.hostname),
+            error: pick(colors.error, self.error),
+        }
+    }
+}
+
+/// Themes that come built in, by name, the first one being the default.
+const BUILTIN_THEMES: &[(&str, Theme)] = &[
+    ("solaara-gold", Theme::single(SOLAARA_GOLD)),
+    ("light-magenta", Theme::single(Color::LightMagenta)),
+    (
+        "nord",
+        Theme {
+            logo: Color::Rgb(136, 192, 208),
+            border: Color::Rgb(94, 129, 172),
+            edge: Color::Rgb(76, 86, 106),
+            title: Color::Rgb(143, 188, 187),
+            body: Color::Rgb(216, 222, 233),
+            hostname: Color::Rgb(235, 203, 139),
+            error: Color::Rgb(191, 97, 106),
+        },
+    ),
+    (
+        "gruvbox",
+        Theme {
+            logo: Color::Rgb(250, 189, 47),
+            border: Color::Rgb(168, 153, 132),
+            edge: Color::Rgb(124, 111, 100),
+            title: Color::Rgb(184, 187, 38),
+            body: Color::Rgb(235, 219, 178),
+            hostname: Color::Rgb(254, 128, 25),
+            error: Color::Rgb(251, 73, 52),
+        },
+    ),
+    (
+        "dracula",
+        Theme {
+            logo: Color::Rgb(189, 147, 249),
+            border: Color::Rgb(98, 114, 164),
+            edge: Color::Rgb(98, 114, 164),
+            title: Color::Rgb(255, 121, 198),
+            body: Color::Rgb(248, 248, 242),
+            hostname: Color::Rgb(139, 233, 253),
+            error: Color::Rgb(255, 85, 85),
+        },
+    ),
+    // follows whatever palette the terminal is set up with
+    (
+        "terminal",
+        Theme {
+            logo: Color::Yellow,
+            border: Color::Blue,
+            edge: Color::DarkGray,
+            title: Color::Cyan,
+            body: Color::Reset,
+            hostname: Color::Green,
+            error: Color::Red,
+        },
+    ),
+];
+
+/// The name of one of the built-in themes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ThemeName(&'static str);
+
+impl ThemeName {
+    pub fn theme(self) -> Theme {
+        BUILTIN_THEMES
+            .iter()
+            .find(|(name, _)| *name == self.0)
+            .map(|(_, theme)| *theme)
+            .expect("theme names are only made from built-in themes")
+    }
+
+    /// Every built-in theme, in the order they're cycled through.
+    pub fn all() -> impl Iterator<Item = ThemeName> {
+        BUILTIN_THEMES.iter().map(|(name, _)| ThemeName(name))
+    }
+}
+
+impl Default for ThemeName {
+    fn default() -> Self {
+        Self(BUILTIN_THEMES[0].0)
+    }
+}
+
+impl FromStr for ThemeName {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        Self::all().find(|theme| theme.0 == name).ok_or_else(|| {
+            let names: Vec<_> = Self::all().map(|theme| theme.0).collect();
+            anyhow!(
+                "Unknown theme `{name}`, expected one of {}.",
+                names.join(", ")
+            )
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeName {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Display for ThemeName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Colors from the config file to use instead of the theme's, each one optional.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ThemeColors {
+    pub logo: Option<ThemeColor>,
+    pub border: Option<ThemeColor>,
+    pub edge: Option<ThemeColor>,
+    pub title: Option<ThemeColor>,
+    pub body: Option<ThemeColor>,
+    pub hostname: Option<ThemeColor>,
+    pub error: Option<ThemeColor>,
+}
+
+/// A color written as `#fff1a4`, `rgb(255, 241, 164)`, a name like `light-magenta` or an
+/// index into the terminal's 256 colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) struct ThemeColor(pub Color);
+
+impl FromStr for ThemeColor {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let rgb = || {
+            let channels = value
+                .trim()
+                .strip_prefix("rgb(")?
+                .strip_suffix(')')?
+                .split(',')
+                .map(|channel| channel.trim().parse().ok())
+                .collect::<Option<Vec<u8>>>()?;
+            match channels[..] {
+                [r, g, b] => Some(Color::Rgb(r, g, b)),
+                _ => None,
+            }
+        };
+
+        if value == "solaara-gold" {
+            return Ok(Self(SOLAARA_GOLD));
+        }
+        // ratatui knows the names, hex codes and indexes
+        value
+            .parse()
+            .ok()
+            .or_else(rgb)
+            .map(Self)
+            .ok_or_else(|| anyhow!("Unknown color `{value}`."))
+    }
+}
+
+impl TryFrom<String> for ThemeColor {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testresult::TestResult;
+
+    #[test]
+    fn test_parse_colors() -> TestResult {
+        for (value, color) in [
+            ("#fff1a4", SOLAARA_GOLD),
+            ("rgb(255, 241, 164)", SOLAARA_GOLD),
+            ("rgb(255,241,164)", SOLAARA_GOLD),
+            ("solaara-gold", SOLAARA_GOLD),
+            ("light-magenta", Color::LightMagenta),
+            ("LightMagenta", Color::LightMagenta),
+            ("dark_gray", Color::DarkGray),
+            ("reset", Color::Reset),
+            ("208", Color::Indexed(208)),
+        ] {
+            assert_eq!(value.parse::<ThemeColor>()?, ThemeColor(color), "{value}");
+        }
+        for value in ["#fff1a", "rgb(255, 241)", "rgb(256, 0, 0)", "256", "gold"] {
+            assert!(value.parse::<ThemeColor>().is_err(), "{value}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_builtin_themes() -> TestResult {
+        let names: Vec<_> = ThemeName::all().map(|name| name.to_string()).collect();
+        for (idx, name) in names.iter().enumerate() {
+            assert!(!names[..idx].contains(name), "{name} is there twice");
+            assert_eq!(name.parse::<ThemeName>()?.to_string(), *name);
+        }
+        assert_eq!(ThemeName::default().theme(), Theme::single(SOLAARA_GOLD));
+
+        let error = "solarized".parse::<ThemeName>().unwrap_err();
+        assert!(error.to_string().starts_with("Unknown theme `solarized`"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_colors() {
+        let colors = ThemeColors {
+            edge: Some(ThemeColor(Color::Indexed(8))),
+            error: Some(ThemeColor(Color::LightRed)),
+            ..Default::default()
+        };
+        let theme = Theme::single(Color::Cyan).with_colors(&colors);
+        assert_eq!(theme.edge, Color::Indexed(8));
+        assert_eq!(theme.error, Color::LightRed);
+        assert_eq!(theme.logo, Color::Cyan);
+    }
+}